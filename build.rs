@@ -3,6 +3,11 @@ use std::fs;
 use std::path::PathBuf;
 
 fn main() {
+    // Without the dashboard there is nothing to bundle.
+    if env::var_os("CARGO_FEATURE_FRONTEND").is_none() {
+        return;
+    }
+
     println!("cargo:rerun-if-changed=frontend/index.html");
     println!("cargo:rerun-if-changed=frontend/styles.css");
     println!("cargo:rerun-if-changed=frontend/app.js");
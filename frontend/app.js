@@ -18,6 +18,12 @@
 	const perfParams = new URLSearchParams(window.location.search);
 	const PERF_DEBUG_ENABLED = perfParams.get("debug") === "perf";
 	const BASE_PATH = (window.__HOSHI_PREFIX__ || "").replace(/\/+$/, "");
+	// `serve --display-tz`; empty means the browser's own zone.
+	const DISPLAY_TZ = window.__HOSHI_DISPLAY_TZ__ || undefined;
+
+	function formatTimestamp(value) {
+		return new Date(value).toLocaleString(undefined, { timeZone: DISPLAY_TZ });
+	}
 
 	function withBasePath(path) {
 		const normalizedPath = path.startsWith("/") ? path : `/${path}`;
@@ -167,6 +173,7 @@
 		filterOptions: {
 			languages: [],
 			activity: [],
+			users: [],
 		},
 		presets: [],
 		activePresetId: null,
@@ -188,11 +195,15 @@
 	}
 
 	const tierLabels = {
+		"very-high": "Very high activity",
 		high: "High activity",
 		medium: "Medium activity",
 		low: "Low activity",
+		dormant: "Dormant",
 		unknown: "Unclassified",
 	};
+	// Known tiers in fastest-first order; custom tiers follow in option order.
+	const tierOrder = ["very-high", "high", "medium", "low", "dormant"];
 
 	let lastAcknowledgedMs = readAckTimestamp();
 	let isApplyingPreset = false;
@@ -819,7 +830,7 @@
 		const ageMs = Date.now() - lastSyncedAt.getTime();
 		const staleThreshold = REFRESH_INTERVAL_MS * 1.5;
 		const isStale = ageMs > staleThreshold;
-		dom.syncStatus.textContent = `Last synced: ${lastSyncedAt.toLocaleString()}`;
+		dom.syncStatus.textContent = `Last synced: ${formatTimestamp(lastSyncedAt)}`;
 		if (isStale) {
 			const ageMinutes = Math.round(ageMs / 60000);
 			dom.staleBadge.textContent =
@@ -1022,9 +1033,14 @@
 			);
 		}
 
-		const activityChips = ["high", "medium", "low"];
-		const availableActivity = new Set(getActivityOptions());
-		if (activityChips.some((tier) => availableActivity.has(tier))) {
+		const availableActivity = getActivityOptions().filter(
+			(tier) => tier !== "unknown",
+		);
+		const activityChips = [
+			...tierOrder.filter((tier) => availableActivity.includes(tier)),
+			...availableActivity.filter((tier) => !tierOrder.includes(tier)),
+		];
+		if (activityChips.length > 0) {
 			const groupLabel = document.createElement("span");
 			groupLabel.className = "quick-filter-label";
 			groupLabel.textContent = "Activity:";
@@ -1062,15 +1078,25 @@
 		}
 	}
 
+	// "Alice Smith (alice)" once /api/options knows the profile name, else the login.
+	function userLabel(login) {
+		const entry = state.filterOptions.users.find(
+			(user) => user.login === login,
+		);
+		const name = entry?.display_name;
+		return name && name !== login ? `${name} (${login})` : login;
+	}
+
 	function renderUserBanner() {
 		if (state.userMode === "none" || !state.userValue) {
 			dom.userFilterBanner.hidden = true;
 			return;
 		}
+		const label = userLabel(state.userValue);
 		const modeText =
 			state.userMode === "pin"
-				? `Showing only stars from ${state.userValue}`
-				: `Hiding stars from ${state.userValue}`;
+				? `Showing only stars from ${label}`
+				: `Hiding stars from ${label}`;
 		dom.userFilterLabel.textContent = modeText;
 		dom.userFilterBanner.hidden = false;
 	}
@@ -1250,7 +1276,7 @@
 		} else {
 			userButton.setAttribute("aria-pressed", "false");
 		}
-		userButton.title = "Click to cycle user filter";
+		userButton.title = `${userLabel(item.login)}: click to cycle user filter`;
 		userButton.addEventListener("click", () => handleUserToggle(item.login));
 		header.appendChild(userButton);
 
@@ -1336,7 +1362,7 @@
 
 		const starredTime = document.createElement("time");
 		starredTime.dateTime = item.starred_at;
-		starredTime.textContent = new Date(item.starred_at).toLocaleString();
+		starredTime.textContent = formatTimestamp(item.starred_at);
 		starredTime.className = "timestamp-value";
 		times.appendChild(starredTime);
 
@@ -1347,7 +1373,7 @@
 
 		const fetchedTime = document.createElement("time");
 		fetchedTime.dateTime = item.fetched_at;
-		fetchedTime.textContent = new Date(item.fetched_at).toLocaleString();
+		fetchedTime.textContent = formatTimestamp(item.fetched_at);
 		fetchedTime.className = "timestamp-value";
 		times.appendChild(fetchedTime);
 
@@ -1812,9 +1838,13 @@
 			state.filterOptions.activity = Array.isArray(payload?.activity_tiers)
 				? payload.activity_tiers
 				: [];
+			state.filterOptions.users = Array.isArray(payload?.users)
+				? payload.users
+				: [];
 			optionsEtag = response.headers.get("ETag") || optionsEtag;
 			populateFilters();
 			computeQuickFilters();
+			renderUserBanner();
 		} catch (err) {
 			console.warn("Failed to fetch filter options", err);
 		}
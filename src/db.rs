@@ -1,15 +1,20 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::OnceLock;
 
 pub mod star_query;
+pub mod writer;
 
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
 use rand::Rng;
 use rusqlite::types::Type;
 use rusqlite::{Connection, Error, OptionalExtension, params};
+use serde::Deserialize;
 
 use crate::{
-    config::Config,
+    config::{Config, IntervalOverride, RepoFilter},
     github::{FollowingUser, StarEvent},
 };
 
@@ -26,15 +31,127 @@ pub struct UserRecord {
     pub activity_tier: Option<String>,
     pub ema_minutes: Option<f64>,
     pub star_count: i64,
+    pub consecutive_failures: i64,
+    /// Set by `[[polling.overrides]]`; replaces the adaptive interval.
+    pub pinned_interval_minutes: Option<i64>,
+    /// Next oldest-first page of a history cut short by `--max-pages-per-user`.
+    pub backfill_page: Option<i64>,
+}
+
+/// SQLite page cache, memory-map size, sync and journal modes applied to every connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqliteTuning {
+    pub cache_kb: u64,
+    pub mmap_mb: u64,
+    pub synchronous: SqliteSynchronous,
+    pub journal_mode: SqliteJournalMode,
+}
+
+impl Default for SqliteTuning {
+    fn default() -> Self {
+        Self {
+            cache_kb: DEFAULT_SQLITE_CACHE_KB,
+            mmap_mb: DEFAULT_SQLITE_MMAP_MB,
+            synchronous: SqliteSynchronous::default(),
+            journal_mode: SqliteJournalMode::default(),
+        }
+    }
+}
+
+/// `PRAGMA synchronous`: how often SQLite waits for writes to reach the disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqliteSynchronous {
+    /// Never sync. Fastest, but a power loss or OS crash can corrupt the database.
+    Off,
+    /// Sync at WAL checkpoints. A crash may drop the last commits but never corrupts.
+    #[default]
+    Normal,
+    /// Sync on every commit, so committed writes survive a power loss.
+    Full,
+}
+
+impl SqliteSynchronous {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SqliteSynchronous::Off => "off",
+            SqliteSynchronous::Normal => "normal",
+            SqliteSynchronous::Full => "full",
+        }
+    }
+}
+
+/// `PRAGMA journal_mode`: how SQLite keeps a transaction recoverable until it commits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqliteJournalMode {
+    /// Write-ahead log in `-wal`/`-shm` files; readers never block the writer.
+    #[default]
+    Wal,
+    /// Rollback journal deleted after each commit; no sidecar files between writes.
+    Delete,
+    /// Rollback journal truncated instead of deleted after each commit.
+    Truncate,
+}
+
+impl SqliteJournalMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SqliteJournalMode::Wal => "wal",
+            SqliteJournalMode::Delete => "delete",
+            SqliteJournalMode::Truncate => "truncate",
+        }
+    }
+}
+
+/// Matches SQLite's built-in default cache of roughly 2 MiB.
+pub const DEFAULT_SQLITE_CACHE_KB: u64 = 2048;
+/// Memory-mapped I/O stays off unless requested.
+pub const DEFAULT_SQLITE_MMAP_MB: u64 = 0;
+/// How long a read watermark outlives its last update: the lifetime of the
+/// cookie naming its client. Maintenance drops older ones.
+pub const WATERMARK_RETENTION_DAYS: i64 = 365;
+/// Watermarks kept at most; storing another drops the least recently updated.
+const MAX_CLIENT_WATERMARKS: i64 = 10_000;
+
+static SQLITE_TUNING: OnceLock<SqliteTuning> = OnceLock::new();
+
+/// Sets the pragmas used by every later connection. Only the first call takes
+/// effect; call it once at startup before touching the database.
+pub fn configure_sqlite(tuning: SqliteTuning) {
+    let _ = SQLITE_TUNING.set(tuning);
+}
+
+/// Opens a connection with the configured cache, mmap, sync, and journal pragmas applied.
+pub(crate) fn open_connection(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    let tuning = SQLITE_TUNING.get().copied().unwrap_or_default();
+    // The journal mode is stored in the file; this only switches it when it differs.
+    conn.pragma_update_and_check(None, "journal_mode", tuning.journal_mode.as_str(), |_| {
+        Ok(())
+    })?;
+    conn.pragma_update(None, "synchronous", tuning.synchronous.as_str())?;
+    // A negative cache_size is interpreted as KiB rather than pages.
+    conn.pragma_update(None, "cache_size", -(tuning.cache_kb as i64))?;
+    if tuning.mmap_mb > 0 {
+        conn.pragma_update(None, "mmap_size", (tuning.mmap_mb * 1024 * 1024) as i64)?;
+    }
+    Ok(conn)
 }
 
 pub async fn init(db_path: &Path) -> Result<()> {
+    init_with_tiers(db_path, &ActivityTiers::default()).await
+}
+
+/// Like [`init`], but classifies users whose stored tier is missing or no
+/// longer configured against `tiers`.
+pub async fn init_with_tiers(db_path: &Path, tiers: &ActivityTiers) -> Result<()> {
     let path = db_path.to_path_buf();
+    let tiers = tiers.clone();
     tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         conn.execute_batch(
             r#"
-            PRAGMA journal_mode = WAL;
             CREATE TABLE IF NOT EXISTS users (
                 user_id INTEGER PRIMARY KEY,
                 login TEXT NOT NULL UNIQUE,
@@ -46,7 +163,13 @@ pub async fn init(db_path: &Path) -> Result<()> {
                 next_check_at TEXT NOT NULL,
                 activity_tier TEXT,
                 ema_minutes REAL,
-                star_count INTEGER NOT NULL DEFAULT 0
+                star_count INTEGER NOT NULL DEFAULT 0,
+                consecutive_failures INTEGER NOT NULL DEFAULT 0,
+                pinned_interval_minutes INTEGER,
+                unfollowed_at TEXT,
+                display_name TEXT,
+                display_name_checked_at TEXT,
+                backfill_page INTEGER
             );
 
             CREATE TABLE IF NOT EXISTS stars (
@@ -57,6 +180,8 @@ pub async fn init(db_path: &Path) -> Result<()> {
                 repo_language TEXT,
                 repo_topics TEXT,
                 repo_html_url TEXT NOT NULL,
+                repo_owner_avatar_url TEXT,
+                repo_pushed_at TEXT,
                 starred_at TEXT NOT NULL,
                 fetched_at TEXT NOT NULL,
                 UNIQUE(user_id, repo_full_name, starred_at)
@@ -64,28 +189,75 @@ pub async fn init(db_path: &Path) -> Result<()> {
 
             CREATE INDEX IF NOT EXISTS idx_stars_user_starred_at ON stars(user_id, starred_at DESC);
             CREATE INDEX IF NOT EXISTS idx_stars_starred_at ON stars(starred_at DESC);
+            CREATE INDEX IF NOT EXISTS idx_stars_fetched_at ON stars(fetched_at);
+
+            CREATE TABLE IF NOT EXISTS repo_topics (
+                star_id INTEGER NOT NULL REFERENCES stars(id) ON DELETE CASCADE,
+                topic TEXT NOT NULL,
+                PRIMARY KEY (star_id, topic)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_repo_topics_topic ON repo_topics(topic);
+
+            CREATE TABLE IF NOT EXISTS hidden_events (
+                user_id INTEGER NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+                repo_full_name TEXT NOT NULL,
+                hidden_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, repo_full_name)
+            );
+
+            CREATE TABLE IF NOT EXISTS client_watermarks (
+                client_id TEXT PRIMARY KEY,
+                seen_sequence INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            );
             "#,
         )?;
 
         ensure_column(&conn, "users", "activity_tier", "TEXT")?;
         ensure_column(&conn, "users", "ema_minutes", "REAL")?;
         ensure_column(&conn, "users", "star_count", "INTEGER")?;
+        ensure_column(
+            &conn,
+            "users",
+            "consecutive_failures",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        ensure_column(&conn, "users", "pinned_interval_minutes", "INTEGER")?;
+        ensure_column(&conn, "users", "unfollowed_at", "TEXT")?;
+        ensure_column(&conn, "users", "display_name", "TEXT")?;
+        ensure_column(&conn, "users", "display_name_checked_at", "TEXT")?;
+        ensure_column(&conn, "users", "backfill_page", "INTEGER")?;
         ensure_column(&conn, "stars", "repo_language", "TEXT")?;
         ensure_column(&conn, "stars", "repo_topics", "TEXT")?;
-
-        // Backfill activity tiers for existing records using current fetch intervals.
+        ensure_column(&conn, "stars", "repo_owner_avatar_url", "TEXT")?;
+        // Stars stored before pushed_at was captured stay NULL ("unknown").
+        ensure_column(&conn, "stars", "repo_pushed_at", "TEXT")?;
+        // Created after the column migrations, which older databases need first.
+        // Lets the `/api/options` language counts group from the index alone.
         conn.execute(
-            "UPDATE users SET activity_tier = 'high' WHERE activity_tier IS NULL AND fetch_interval_minutes <= 60",
-            [],
-        )?;
-        conn.execute(
-            "UPDATE users SET activity_tier = 'medium' WHERE activity_tier IS NULL AND fetch_interval_minutes > 60 AND fetch_interval_minutes <= 1440",
-            [],
-        )?;
-        conn.execute(
-            "UPDATE users SET activity_tier = 'low' WHERE activity_tier IS NULL AND fetch_interval_minutes > 1440",
+            "CREATE INDEX IF NOT EXISTS idx_stars_language ON stars(repo_language)",
             [],
         )?;
+
+        // Populate the topic join table for stars stored before it existed. The
+        // JSON column stays authoritative for rendering.
+        conn.execute(INDEX_MISSING_TOPICS_SQL, [])?;
+
+        // Backfill activity tiers for existing records using current fetch
+        // intervals, fastest tier first so each user lands in the first match.
+        // Tiers dropped from the configuration are reclassified the same way.
+        let known = serde_json::to_string(&tiers.names().collect::<Vec<_>>())
+            .expect("tier names serialize");
+        for tier in tiers.iter() {
+            conn.execute(
+                "UPDATE users SET activity_tier = ?1
+                 WHERE (activity_tier IS NULL
+                        OR activity_tier NOT IN (SELECT value FROM json_each(?2)))
+                   AND (?3 IS NULL OR fetch_interval_minutes <= ?3)",
+                params![tier.name, known, tier.max_interval_minutes],
+            )?;
+        }
         conn.execute(
             "UPDATE users SET star_count = 0 WHERE star_count IS NULL",
             [],
@@ -102,26 +274,38 @@ pub async fn init(db_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Adds `repo_topics` rows for stars that have none yet.
+const INDEX_MISSING_TOPICS_SQL: &str = "INSERT OR IGNORE INTO repo_topics (star_id, topic)
+     SELECT s.id, LOWER(j.value)
+     FROM stars s, json_each(s.repo_topics) j
+     WHERE s.repo_topics IS NOT NULL
+       AND json_valid(s.repo_topics)
+       AND NOT EXISTS (SELECT 1 FROM repo_topics t WHERE t.star_id = s.id)";
+
+/// Adds new followings, due at once, in `initial_tier` until their first
+/// recompute; known users only get their login refreshed.
 pub async fn upsert_followings(
     db_path: &Path,
     users: &[FollowingUser],
     initial_interval_minutes: i64,
+    initial_tier: &str,
 ) -> Result<()> {
     if users.is_empty() {
         return Ok(());
     }
     let path = db_path.to_path_buf();
     let users = users.to_owned();
+    let initial_tier = initial_tier.to_string();
     tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
-        let mut conn = Connection::open(path)?;
+        let mut conn = open_connection(&path)?;
         let now = Utc::now().to_rfc3339();
         let tx = conn.transaction()?;
         for user in users {
             tx.execute(
                 "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count)
-                 VALUES (?1, ?2, NULL, NULL, NULL, NULL, ?3, ?4, 'low', NULL, 0)
-                 ON CONFLICT(user_id) DO UPDATE SET login = excluded.login",
-                params![user.id, user.login, initial_interval_minutes, now],
+                 VALUES (?1, ?2, NULL, NULL, NULL, NULL, ?3, ?4, ?5, NULL, 0)
+                 ON CONFLICT(user_id) DO UPDATE SET login = excluded.login, unfollowed_at = NULL",
+                params![user.id, user.login, initial_interval_minutes, now, initial_tier],
             )?;
         }
         tx.commit()?;
@@ -131,38 +315,142 @@ pub async fn upsert_followings(
     Ok(())
 }
 
+/// Marks every user missing from `tracked` as unfollowed, which stops them
+/// being polled while their stars stay in the feed. Re-following clears the
+/// mark through [`upsert_followings`]. Returns how many users were newly marked.
+pub async fn mark_unfollowed(
+    db_path: &Path,
+    tracked: &[FollowingUser],
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let path = db_path.to_path_buf();
+    let ids = serde_json::to_string(&tracked.iter().map(|user| user.id).collect::<Vec<_>>())?;
+    let marked = tokio::task::spawn_blocking(move || -> rusqlite::Result<usize> {
+        let conn = open_connection(&path)?;
+        conn.execute(
+            "UPDATE users SET unfollowed_at = ?1
+             WHERE unfollowed_at IS NULL
+               AND user_id NOT IN (SELECT value FROM json_each(?2))",
+            params![now.to_rfc3339(), ids],
+        )
+    })
+    .await??;
+    Ok(marked)
+}
+
+/// Followed users whose profile name has never been looked up, as
+/// `(user_id, login)`, at most `limit` of them.
+pub async fn users_missing_display_name(
+    db_path: &Path,
+    limit: usize,
+) -> Result<Vec<(i64, String)>> {
+    let path = db_path.to_path_buf();
+    let users = tokio::task::spawn_blocking(move || -> rusqlite::Result<Vec<(i64, String)>> {
+        let conn = open_connection(&path)?;
+        let mut stmt = conn.prepare(
+            "SELECT user_id, login FROM users
+             WHERE unfollowed_at IS NULL AND display_name_checked_at IS NULL
+             ORDER BY login ASC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    })
+    .await??;
+    Ok(users)
+}
+
+/// Stores a looked-up profile name; `None` records that the user has none so
+/// they are not asked about again.
+pub async fn record_display_name(
+    db_path: &Path,
+    user_id: i64,
+    name: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let path = db_path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
+        let conn = open_connection(&path)?;
+        conn.execute(
+            "UPDATE users SET display_name = ?2, display_name_checked_at = ?3 WHERE user_id = ?1",
+            params![user_id, name, now.to_rfc3339()],
+        )?;
+        Ok(())
+    })
+    .await??;
+    Ok(())
+}
+
+/// Stores the configured pins on their users and clears pins no longer
+/// configured. A newly pinned user is rescheduled from its last fetch so the
+/// pin takes effect without waiting out the adaptive interval.
+pub async fn apply_interval_pins(
+    db_path: &Path,
+    pins: &[IntervalOverride],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let path = db_path.to_path_buf();
+    let pins: HashMap<String, i64> = pins
+        .iter()
+        .map(|pin| (pin.login.to_lowercase(), pin.interval_minutes))
+        .collect();
+    tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
+        let mut conn = open_connection(&path)?;
+        let tx = conn.transaction()?;
+        let current = {
+            let mut stmt =
+                tx.prepare("SELECT user_id, login, pinned_interval_minutes, last_fetched_at FROM users")?;
+            stmt.query_map([], |row| {
+                let last_fetched: Option<String> = row.get(3)?;
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    parse_optional_datetime_sql(last_fetched, 3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (user_id, login, pinned, last_fetched_at) in current {
+            let wanted = pins.get(&login.to_lowercase()).copied();
+            if wanted == pinned {
+                continue;
+            }
+            match wanted {
+                Some(minutes) => {
+                    let next = next_check_with_jitter(last_fetched_at.unwrap_or(now), minutes);
+                    tx.execute(
+                        "UPDATE users SET pinned_interval_minutes = ?1, fetch_interval_minutes = ?1, next_check_at = ?2
+                         WHERE user_id = ?3",
+                        params![minutes, next.to_rfc3339(), user_id],
+                    )?;
+                }
+                None => {
+                    tx.execute(
+                        "UPDATE users SET pinned_interval_minutes = NULL WHERE user_id = ?1",
+                        [user_id],
+                    )?;
+                }
+            }
+        }
+        tx.commit()
+    })
+    .await??;
+    Ok(())
+}
+
 pub async fn due_users(db_path: &Path, now: DateTime<Utc>) -> Result<Vec<UserRecord>> {
     let path = db_path.to_path_buf();
     let now_string = now.to_rfc3339();
     let users = tokio::task::spawn_blocking(move || -> rusqlite::Result<Vec<UserRecord>> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         let mut stmt = conn.prepare(
-            "SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count
+            "SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count, consecutive_failures, pinned_interval_minutes, backfill_page
              FROM users
-             WHERE next_check_at <= ?1
+             WHERE next_check_at <= ?1 AND unfollowed_at IS NULL
              ORDER BY next_check_at ASC",
         )?;
-        let rows = stmt.query_map([now_string], |row| {
-            let next_check_at_raw: String = row.get(7)?;
-            let last_starred_at_raw: Option<String> = row.get(2)?;
-            let last_fetched_at_raw: Option<String> = row.get(3)?;
-            let last_starred_at = parse_optional_datetime_sql(last_starred_at_raw, 2)?;
-            let last_fetched_at = parse_optional_datetime_sql(last_fetched_at_raw, 3)?;
-            let next_check_at = parse_datetime_sql(&next_check_at_raw, 7)?;
-            Ok(UserRecord {
-                user_id: row.get(0)?,
-                login: row.get(1)?,
-                last_starred_at,
-                last_fetched_at,
-                etag: row.get(4)?,
-                last_modified: row.get(5)?,
-                fetch_interval_minutes: row.get(6)?,
-                next_check_at,
-                activity_tier: row.get(8)?,
-                ema_minutes: row.get(9)?,
-                star_count: row.get(10)?,
-            })
-        })?;
+        let rows = stmt.query_map([now_string], user_record_from_row)?;
         let mut users = Vec::new();
         for record in rows {
             users.push(record?);
@@ -173,19 +461,228 @@ pub async fn due_users(db_path: &Path, now: DateTime<Utc>) -> Result<Vec<UserRec
     Ok(users)
 }
 
+pub async fn has_any_stars(db_path: &Path) -> Result<bool> {
+    let path = db_path.to_path_buf();
+    let exists = tokio::task::spawn_blocking(move || -> rusqlite::Result<bool> {
+        let conn = open_connection(&path)?;
+        conn.query_row("SELECT EXISTS(SELECT 1 FROM stars)", [], |row| row.get(0))
+    })
+    .await??;
+    Ok(exists)
+}
+
+/// Result of `PRAGMA wal_checkpoint`: whether a reader or writer blocked it,
+/// the WAL size in frames, and how many frames were copied into the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalCheckpoint {
+    pub busy: bool,
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+/// Copies the WAL into the main database and truncates the `-wal` file.
+pub async fn checkpoint_wal(db_path: &Path) -> Result<WalCheckpoint> {
+    let path = db_path.to_path_buf();
+    let checkpoint = tokio::task::spawn_blocking(move || -> rusqlite::Result<WalCheckpoint> {
+        let conn = open_connection(&path)?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok(WalCheckpoint {
+                busy: row.get::<_, i64>(0)? != 0,
+                log_frames: row.get(1)?,
+                checkpointed_frames: row.get(2)?,
+            })
+        })
+    })
+    .await??;
+    Ok(checkpoint)
+}
+
+/// Outcome of [`run_maintenance`]: database size before and after, expired
+/// watermarks dropped, and the closing WAL checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub watermarks_pruned: usize,
+    pub checkpoint: WalCheckpoint,
+}
+
+impl MaintenanceReport {
+    pub fn freed_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Drops expired read watermarks, rebuilds the database to drop free pages,
+/// refreshes planner statistics, and truncates the WAL. `VACUUM` needs
+/// exclusive access, so this blocks writers for as long as the rebuild takes.
+pub async fn run_maintenance(db_path: &Path) -> Result<MaintenanceReport> {
+    let path = db_path.to_path_buf();
+    let expired = (Utc::now() - chrono::Duration::days(WATERMARK_RETENTION_DAYS)).to_rfc3339();
+    let report = tokio::task::spawn_blocking(move || -> rusqlite::Result<MaintenanceReport> {
+        let conn = open_connection(&path)?;
+        let database_bytes = |conn: &Connection| -> rusqlite::Result<u64> {
+            let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+            let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+            Ok((pages * page_size) as u64)
+        };
+        let bytes_before = database_bytes(&conn)?;
+        let watermarks_pruned = conn.execute(
+            "DELETE FROM client_watermarks WHERE updated_at < ?1",
+            [expired],
+        )?;
+        conn.execute_batch("VACUUM; ANALYZE;")?;
+        let checkpoint = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok(WalCheckpoint {
+                busy: row.get::<_, i64>(0)? != 0,
+                log_frames: row.get(1)?,
+                checkpointed_frames: row.get(2)?,
+            })
+        })?;
+        Ok(MaintenanceReport {
+            bytes_before,
+            bytes_after: database_bytes(&conn)?,
+            watermarks_pruned,
+            checkpoint,
+        })
+    })
+    .await??;
+    Ok(report)
+}
+
+/// On-disk footprint and row counts, for capacity reporting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatabaseStats {
+    /// The database file plus its `-wal` and `-shm` siblings.
+    pub size_bytes: u64,
+    pub total_stars: u64,
+    pub total_users: u64,
+}
+
+pub async fn database_stats(db_path: &Path) -> Result<DatabaseStats> {
+    let path = db_path.to_path_buf();
+    let stats = tokio::task::spawn_blocking(move || -> Result<DatabaseStats> {
+        let conn = open_connection(&path)?;
+        let count = |table: &str| -> rusqlite::Result<u64> {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count.max(0) as u64)
+        };
+        let total_stars = count("stars")?;
+        let total_users = count("users")?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let size_bytes = [
+            path.clone(),
+            path.with_file_name(format!("{file_name}-wal")),
+            path.with_file_name(format!("{file_name}-shm")),
+        ]
+        .iter()
+        .filter_map(|file| std::fs::metadata(file).ok())
+        .map(|meta| meta.len())
+        .sum();
+        Ok(DatabaseStats {
+            size_bytes,
+            total_stars,
+            total_users,
+        })
+    })
+    .await??;
+    Ok(stats)
+}
+
+/// Every user that is still followed, for `--backfill`.
+pub async fn followed_users(db_path: &Path) -> Result<Vec<UserRecord>> {
+    let path = db_path.to_path_buf();
+    let users = tokio::task::spawn_blocking(move || -> rusqlite::Result<Vec<UserRecord>> {
+        let conn = open_connection(&path)?;
+        let mut stmt = conn.prepare(
+            "SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count, consecutive_failures, pinned_interval_minutes, backfill_page
+             FROM users
+             WHERE unfollowed_at IS NULL
+             ORDER BY login ASC",
+        )?;
+        let rows = stmt.query_map([], user_record_from_row)?;
+        rows.collect()
+    })
+    .await??;
+    Ok(users)
+}
+
+pub async fn all_users(db_path: &Path) -> Result<Vec<UserRecord>> {
+    let path = db_path.to_path_buf();
+    let users = tokio::task::spawn_blocking(move || -> rusqlite::Result<Vec<UserRecord>> {
+        let conn = open_connection(&path)?;
+        let mut stmt = conn.prepare(
+            "SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count, consecutive_failures, pinned_interval_minutes, backfill_page
+             FROM users
+             ORDER BY login ASC",
+        )?;
+        let rows = stmt.query_map([], user_record_from_row)?;
+        rows.collect()
+    })
+    .await??;
+    Ok(users)
+}
+
+/// Looks up one user by login, ignoring case.
+pub async fn user_by_login(db_path: &Path, login: &str) -> Result<Option<UserRecord>> {
+    let path = db_path.to_path_buf();
+    let login = login.to_string();
+    let user = tokio::task::spawn_blocking(move || -> rusqlite::Result<Option<UserRecord>> {
+        let conn = open_connection(&path)?;
+        conn.query_row(
+            "SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count, consecutive_failures, pinned_interval_minutes, backfill_page
+             FROM users
+             WHERE LOWER(login) = LOWER(?1)",
+            [login],
+            user_record_from_row,
+        )
+        .optional()
+    })
+    .await??;
+    Ok(user)
+}
+
+fn user_record_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<UserRecord> {
+    let next_check_at_raw: String = row.get(7)?;
+    let last_starred_at_raw: Option<String> = row.get(2)?;
+    let last_fetched_at_raw: Option<String> = row.get(3)?;
+    let last_starred_at = parse_optional_datetime_sql(last_starred_at_raw, 2)?;
+    let last_fetched_at = parse_optional_datetime_sql(last_fetched_at_raw, 3)?;
+    let next_check_at = parse_datetime_sql(&next_check_at_raw, 7)?;
+    Ok(UserRecord {
+        user_id: row.get(0)?,
+        login: row.get(1)?,
+        last_starred_at,
+        last_fetched_at,
+        etag: row.get(4)?,
+        last_modified: row.get(5)?,
+        fetch_interval_minutes: row.get(6)?,
+        next_check_at,
+        activity_tier: row.get(8)?,
+        ema_minutes: row.get(9)?,
+        star_count: row.get(10)?,
+        consecutive_failures: row.get(11)?,
+        pinned_interval_minutes: row.get(12)?,
+        backfill_page: row.get(13)?,
+    })
+}
+
 pub async fn record_not_modified(
     db_path: &Path,
     user_id: i64,
     fetched_at: DateTime<Utc>,
     interval_minutes: i64,
+    poll_interval: Option<std::time::Duration>,
 ) -> Result<()> {
     let path = db_path.to_path_buf();
     let fetched = fetched_at.to_rfc3339();
-    let next = next_check_with_jitter(fetched_at, interval_minutes).to_rfc3339();
+    let next = schedule_next_check(fetched_at, interval_minutes, poll_interval).to_rfc3339();
     tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         conn.execute(
-            "UPDATE users SET last_fetched_at = ?1, next_check_at = ?2 WHERE user_id = ?3",
+            "UPDATE users SET last_fetched_at = ?1, next_check_at = ?2, consecutive_failures = 0 WHERE user_id = ?3",
             params![fetched, next, user_id],
         )?;
         Ok(())
@@ -194,12 +691,57 @@ pub async fn record_not_modified(
     Ok(())
 }
 
+/// Clears a user's ETag and Last-Modified and makes them due now, so the next
+/// poll fetches their stars without conditional headers. Returns whether a
+/// tracked user matched `login` (case-insensitively).
+pub async fn reset_validators(db_path: &Path, login: &str, now: DateTime<Utc>) -> Result<bool> {
+    let path = db_path.to_path_buf();
+    let login = login.to_string();
+    let updated = tokio::task::spawn_blocking(move || -> rusqlite::Result<usize> {
+        let conn = open_connection(&path)?;
+        conn.execute(
+            "UPDATE users SET etag = NULL, last_modified = NULL, next_check_at = ?1
+             WHERE LOWER(login) = LOWER(?2)",
+            params![now.to_rfc3339(), login],
+        )
+    })
+    .await??;
+    Ok(updated > 0)
+}
+
+/// Records where a user's oldest-first walk continues, making them due at
+/// `now` so the next poll picks it up; `None` marks the history complete.
+pub async fn record_backfill_page(
+    db_path: &Path,
+    user_id: i64,
+    page: Option<usize>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let path = db_path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
+        let conn = open_connection(&path)?;
+        match page {
+            Some(page) => conn.execute(
+                "UPDATE users SET backfill_page = ?1, next_check_at = ?2 WHERE user_id = ?3",
+                params![page as i64, now.to_rfc3339(), user_id],
+            )?,
+            None => conn.execute(
+                "UPDATE users SET backfill_page = NULL WHERE user_id = ?1",
+                [user_id],
+            )?,
+        };
+        Ok(())
+    })
+    .await??;
+    Ok(())
+}
+
 pub async fn defer_user(db_path: &Path, user_id: i64, wait: std::time::Duration) -> Result<()> {
     let path = db_path.to_path_buf();
     let chrono_wait =
         Duration::from_std(wait).map_err(|e| anyhow!("invalid wait duration: {e}"))?;
     tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         let mut stmt = conn
             .prepare("SELECT COALESCE(fetch_interval_minutes, 0) FROM users WHERE user_id = ?1")?;
         let interval: Option<i64> = stmt.query_row([user_id], |row| row.get(0)).optional()?;
@@ -222,6 +764,260 @@ pub async fn defer_user(db_path: &Path, user_id: i64, wait: std::time::Duration)
     Ok(())
 }
 
+/// Records a 404 for a user (renamed, deleted, or otherwise gone) and parks them
+/// in `parked_tier` until the next max-interval check. Returns the updated
+/// failure streak.
+pub async fn record_user_not_found(
+    db_path: &Path,
+    user_id: i64,
+    max_interval_minutes: i64,
+    parked_tier: &str,
+) -> Result<i64> {
+    let path = db_path.to_path_buf();
+    let parked_tier = parked_tier.to_string();
+    let now = Utc::now();
+    let next = (now + Duration::minutes(max_interval_minutes.max(1))).to_rfc3339();
+    let failures = tokio::task::spawn_blocking(move || -> rusqlite::Result<i64> {
+        let conn = open_connection(&path)?;
+        conn.execute(
+            "UPDATE users SET consecutive_failures = consecutive_failures + 1, last_fetched_at = ?1,
+             next_check_at = ?2, activity_tier = ?3
+             WHERE user_id = ?4",
+            params![now.to_rfc3339(), next, parked_tier, user_id],
+        )?;
+        conn.query_row(
+            "SELECT consecutive_failures FROM users WHERE user_id = ?1",
+            [user_id],
+            |row| row.get(0),
+        )
+    })
+    .await??;
+    Ok(failures)
+}
+
+/// Deletes the star with id `star_id` and remembers its user and repository in
+/// `hidden_events`, so later polls don't store it again. Any other stored star
+/// of the same repository by the same user goes too. Returns `false` when no
+/// such star exists.
+pub async fn hide_star(db_path: &Path, star_id: i64, now: DateTime<Utc>) -> Result<bool> {
+    let path = db_path.to_path_buf();
+    let hidden = tokio::task::spawn_blocking(move || -> rusqlite::Result<bool> {
+        let mut conn = open_connection(&path)?;
+        let tx = conn.transaction()?;
+        let Some((user_id, repo)) = tx
+            .query_row(
+                "SELECT user_id, repo_full_name FROM stars WHERE id = ?1",
+                [star_id],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?
+        else {
+            return Ok(false);
+        };
+        tx.execute(
+            "INSERT OR IGNORE INTO hidden_events (user_id, repo_full_name, hidden_at) VALUES (?1, ?2, ?3)",
+            params![user_id, repo, now.to_rfc3339()],
+        )?;
+        tx.execute(
+            "DELETE FROM repo_topics WHERE star_id IN
+                 (SELECT id FROM stars WHERE user_id = ?1 AND repo_full_name = ?2)",
+            params![user_id, repo],
+        )?;
+        tx.execute(
+            "DELETE FROM stars WHERE user_id = ?1 AND repo_full_name = ?2",
+            params![user_id, repo],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    })
+    .await??;
+    Ok(hidden)
+}
+
+/// Stores the `ingest_sequence` a client has read up to, or with `None` the
+/// newest one stored, then trims the table to [`MAX_CLIENT_WATERMARKS`].
+/// Returns the watermark that was saved.
+pub async fn store_watermark(
+    db_path: &Path,
+    client_id: &str,
+    seen_sequence: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<i64> {
+    let path = db_path.to_path_buf();
+    let client_id = client_id.to_string();
+    let stored = tokio::task::spawn_blocking(move || -> rusqlite::Result<i64> {
+        let conn = open_connection(&path)?;
+        let seen_sequence = match seen_sequence {
+            Some(sequence) => sequence,
+            None => conn.query_row("SELECT COALESCE(MAX(id), 0) FROM stars", [], |row| {
+                row.get(0)
+            })?,
+        };
+        conn.execute(
+            "INSERT INTO client_watermarks (client_id, seen_sequence, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(client_id) DO UPDATE SET seen_sequence = excluded.seen_sequence, updated_at = excluded.updated_at",
+            params![client_id, seen_sequence, now.to_rfc3339()],
+        )?;
+        conn.execute(
+            "DELETE FROM client_watermarks WHERE client_id IN (
+                 SELECT client_id FROM client_watermarks
+                 ORDER BY updated_at DESC, client_id LIMIT -1 OFFSET ?1
+             )",
+            [MAX_CLIENT_WATERMARKS],
+        )?;
+        Ok(seen_sequence)
+    })
+    .await??;
+    Ok(stored)
+}
+
+/// The watermark last stored for `client_id`, if any.
+pub async fn watermark(db_path: &Path, client_id: &str) -> Result<Option<i64>> {
+    let path = db_path.to_path_buf();
+    let client_id = client_id.to_string();
+    let seen = tokio::task::spawn_blocking(move || -> rusqlite::Result<Option<i64>> {
+        let conn = open_connection(&path)?;
+        conn.query_row(
+            "SELECT seen_sequence FROM client_watermarks WHERE client_id = ?1",
+            [client_id],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+    .await??;
+    Ok(seen)
+}
+
+/// Outcome of [`import_database`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Users that were not tracked here before.
+    pub users_added: usize,
+    /// Users matched to one already tracked here.
+    pub users_merged: usize,
+    pub stars_added: usize,
+}
+
+/// Merges the users and stars of another database into this one. Users are
+/// matched by login, case-insensitively, since the two databases may have
+/// tracked the same account under different ids; an unmatched user whose id is
+/// already taken here is the same GitHub account renamed and merges into it.
+/// A merged user keeps its own polling state, gaining the later
+/// `last_starred_at` and a missing display name. Stars already stored or hidden
+/// here are skipped, and stars hidden there are removed here too.
+pub async fn import_database(db_path: &Path, source: &Path) -> Result<ImportReport> {
+    if db_path.canonicalize().ok() == Some(source.canonicalize()?) {
+        return Err(anyhow!("cannot import a database into itself"));
+    }
+    let path = db_path.to_path_buf();
+    let source = source.to_string_lossy().into_owned();
+    let report = tokio::task::spawn_blocking(move || -> rusqlite::Result<ImportReport> {
+        let mut conn = open_connection(&path)?;
+        conn.execute("ATTACH DATABASE ?1 AS source", [&source])?;
+        let mut report = ImportReport::default();
+        let tx = conn.transaction()?;
+        tx.execute_batch(
+            "CREATE TEMP TABLE import_users (source_id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL)",
+        )?;
+        let imported: Vec<(i64, String, Option<String>, Option<String>)> = tx
+            .prepare("SELECT user_id, login, last_starred_at, display_name FROM source.users")?
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        for (source_id, login, last_starred, display_name) in imported {
+            let by_login = tx
+                .query_row(
+                    "SELECT user_id, last_starred_at FROM main.users WHERE LOWER(login) = LOWER(?1)",
+                    [&login],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let existing: Option<(i64, Option<String>)> = match by_login {
+                Some(found) => Some(found),
+                None => tx
+                    .query_row(
+                        "SELECT user_id, last_starred_at FROM main.users WHERE user_id = ?1",
+                        [source_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?,
+            };
+            let user_id = match existing {
+                Some((user_id, current)) => {
+                    let current = parse_optional_datetime_sql(current, 1)?;
+                    let theirs = parse_optional_datetime_sql(last_starred, 2)?;
+                    let latest = current.max(theirs).map(|dt| dt.to_rfc3339());
+                    tx.execute(
+                        "UPDATE main.users
+                         SET last_starred_at = ?2, display_name = COALESCE(display_name, ?3)
+                         WHERE user_id = ?1",
+                        params![user_id, latest, display_name],
+                    )?;
+                    report.users_merged += 1;
+                    user_id
+                }
+                None => {
+                    tx.execute(
+                        "INSERT INTO main.users (user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count, consecutive_failures, pinned_interval_minutes, unfollowed_at, display_name, display_name_checked_at, backfill_page)
+                         SELECT user_id, login, last_starred_at, last_fetched_at, etag, last_modified, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, 0, consecutive_failures, pinned_interval_minutes, unfollowed_at, display_name, display_name_checked_at, backfill_page
+                         FROM source.users WHERE user_id = ?1",
+                        [source_id],
+                    )?;
+                    report.users_added += 1;
+                    source_id
+                }
+            };
+            tx.execute(
+                "INSERT INTO temp.import_users (source_id, user_id) VALUES (?1, ?2)",
+                params![source_id, user_id],
+            )?;
+        }
+
+        tx.execute(
+            "INSERT OR IGNORE INTO main.hidden_events (user_id, repo_full_name, hidden_at)
+             SELECT m.user_id, h.repo_full_name, h.hidden_at
+             FROM source.hidden_events h
+             JOIN temp.import_users m ON m.source_id = h.user_id",
+            [],
+        )?;
+        tx.execute(
+            "DELETE FROM main.stars
+             WHERE EXISTS (SELECT 1 FROM main.hidden_events h
+                           WHERE h.user_id = stars.user_id AND h.repo_full_name = stars.repo_full_name)",
+            [],
+        )?;
+        tx.execute(
+            "DELETE FROM main.repo_topics
+             WHERE star_id NOT IN (SELECT id FROM main.stars)",
+            [],
+        )?;
+        report.stars_added = tx.execute(
+            "INSERT OR IGNORE INTO main.stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, repo_owner_avatar_url, repo_pushed_at, starred_at, fetched_at)
+             SELECT m.user_id, s.repo_full_name, s.repo_description, s.repo_language, s.repo_topics, s.repo_html_url, s.repo_owner_avatar_url, s.repo_pushed_at, s.starred_at, s.fetched_at
+             FROM source.stars s
+             JOIN temp.import_users m ON m.source_id = s.user_id
+             WHERE NOT EXISTS (SELECT 1 FROM main.hidden_events h
+                               WHERE h.user_id = m.user_id AND h.repo_full_name = s.repo_full_name)
+             ORDER BY s.id",
+            [],
+        )?;
+        tx.execute(INDEX_MISSING_TOPICS_SQL, [])?;
+        tx.execute(
+            "UPDATE main.users SET star_count = (
+                 SELECT COUNT(*) FROM main.stars WHERE stars.user_id = users.user_id
+             )",
+            [],
+        )?;
+        tx.execute_batch("DROP TABLE temp.import_users")?;
+        tx.commit()?;
+        conn.execute("DETACH DATABASE source", [])?;
+        Ok(report)
+    })
+    .await??;
+    Ok(report)
+}
+
 pub async fn insert_star_events(
     db_path: &Path,
     user: &UserRecord,
@@ -231,172 +1027,217 @@ pub async fn insert_star_events(
     last_modified: Option<String>,
     config: &Config,
 ) -> Result<i64> {
-    if events.is_empty() {
+    let path = db_path.to_path_buf();
+    let batch = StarBatch {
+        user: user.clone(),
+        events: events.to_owned(),
+        fetched_at,
+        etag,
+        last_modified,
+        poll_interval: None,
+    };
+    let settings = WriteSettings::from(config);
+    let interval = tokio::task::spawn_blocking(move || -> rusqlite::Result<i64> {
+        let mut conn = open_connection(&path)?;
+        let tx = conn.transaction()?;
+        let interval = write_star_batch(&tx, &batch, &settings)?;
+        tx.commit()?;
+        Ok(interval)
+    })
+    .await??;
+    Ok(interval)
+}
+
+/// One user's fetch result, ready to be written.
+#[derive(Debug, Clone)]
+pub struct StarBatch {
+    pub user: UserRecord,
+    pub events: Vec<StarEvent>,
+    pub fetched_at: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// GitHub's `X-Poll-Interval`; the next check is never scheduled sooner.
+    pub poll_interval: Option<std::time::Duration>,
+}
+
+/// The parts of [`Config`] the write path needs, cheap to move onto a blocking thread.
+#[derive(Debug, Clone)]
+pub(crate) struct WriteSettings {
+    min_interval: i64,
+    max_interval: i64,
+    default_interval: i64,
+    ema_alpha: f64,
+    activity_tiers: ActivityTiers,
+    repo_filter: RepoFilter,
+}
+
+impl From<&Config> for WriteSettings {
+    fn from(config: &Config) -> Self {
+        Self {
+            min_interval: config.min_interval_minutes,
+            max_interval: config.max_interval_minutes,
+            default_interval: config.default_interval_minutes,
+            ema_alpha: config.ema_alpha,
+            activity_tiers: config.activity_tiers.clone(),
+            repo_filter: config.repo_filter.clone(),
+        }
+    }
+}
+
+/// Stores a user's new stars and reschedules them, returning the new polling
+/// interval. Runs inside the caller's transaction so several users can share one.
+pub(crate) fn write_star_batch(
+    conn: &Connection,
+    batch: &StarBatch,
+    settings: &WriteSettings,
+) -> rusqlite::Result<i64> {
+    let user = &batch.user;
+    if batch.events.is_empty() {
         // Even if there are no events, update metadata to refresh next_check_at
-        update_after_events(
-            db_path,
-            user,
-            user.last_starred_at,
-            fetched_at,
-            etag,
-            last_modified,
-            config,
-            0,
-            &[],
-        )
-        .await?;
-        return Ok(user.fetch_interval_minutes);
+        return update_after_events(conn, batch, settings, user.last_starred_at, 0, &[]);
     }
 
-    let path = db_path.to_path_buf();
     let user_id = user.user_id;
-    let fetched = fetched_at.to_rfc3339();
-    let events_vec = events.to_owned();
-    let etag_clone = etag.clone();
-    let last_modified_clone = last_modified.clone();
-    let inserted_count = tokio::task::spawn_blocking(move || -> rusqlite::Result<i64> {
-        let mut conn = Connection::open(path)?;
-        let tx = conn.transaction()?;
-        let mut inserted = 0i64;
-        for event in &events_vec {
-            let topics_json = if event.repo_topics.is_empty() {
-                None
-            } else {
-                serde_json::to_string(&event.repo_topics).ok()
-            };
-            inserted += tx.execute(
-                "INSERT OR IGNORE INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                params![
-                    user_id,
-                    event.repo_full_name,
-                    event.repo_description,
-                    event.repo_language,
-                    topics_json,
-                    event.repo_html_url,
-                    event.starred_at.to_rfc3339(),
-                    fetched
-                ],
-            )? as i64;
+    let fetched = batch.fetched_at.to_rfc3339();
+    let mut inserted = 0i64;
+    for event in &batch.events {
+        if !settings.repo_filter.is_allowed(&event.repo_full_name) {
+            continue;
         }
-        if let Some(max_starred) = events_vec.iter().map(|e| e.starred_at).max() {
-            tx.execute(
-                "UPDATE users SET last_starred_at = ?1 WHERE user_id = ?2 AND (
-                     last_starred_at IS NULL OR last_starred_at < ?1
-                 )",
-                params![max_starred.to_rfc3339(), user_id],
-            )?;
+        let hidden: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM hidden_events WHERE user_id = ?1 AND repo_full_name = ?2)",
+            params![user_id, event.repo_full_name],
+            |row| row.get(0),
+        )?;
+        if hidden {
+            continue;
         }
-        if let Some(tag) = etag_clone {
-            tx.execute(
-                "UPDATE users SET etag = ?1 WHERE user_id = ?2",
-                params![tag, user_id],
+        // An estimated time differs on every poll, so the unique key alone
+        // would store the same star again; match on the repository instead.
+        if event.starred_at_estimated {
+            let known: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM stars WHERE user_id = ?1 AND repo_full_name = ?2)",
+                params![user_id, event.repo_full_name],
+                |row| row.get(0),
             )?;
+            if known {
+                continue;
+            }
         }
-        if let Some(modified) = last_modified_clone {
-            tx.execute(
-                "UPDATE users SET last_modified = ?1 WHERE user_id = ?2",
-                params![modified, user_id],
-            )?;
+        let topics_json = if event.repo_topics.is_empty() {
+            None
+        } else {
+            serde_json::to_string(&event.repo_topics).ok()
+        };
+        let added = conn.execute(
+            "INSERT OR IGNORE INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, repo_owner_avatar_url, repo_pushed_at, starred_at, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                user_id,
+                event.repo_full_name,
+                event.repo_description,
+                event.repo_language,
+                topics_json,
+                event.repo_html_url,
+                event.repo_owner_avatar_url,
+                event.repo_pushed_at.map(|ts| ts.to_rfc3339()),
+                event.starred_at.to_rfc3339(),
+                fetched
+            ],
+        )?;
+        if added > 0 {
+            let star_id = conn.last_insert_rowid();
+            for topic in &event.repo_topics {
+                conn.execute(
+                    "INSERT OR IGNORE INTO repo_topics (star_id, topic) VALUES (?1, ?2)",
+                    params![star_id, topic.to_lowercase()],
+                )?;
+            }
         }
-        tx.execute(
-            "UPDATE users SET last_fetched_at = ?1 WHERE user_id = ?2",
-            params![fetched, user_id],
+        inserted += added as i64;
+    }
+    if let Some(max_starred) = batch
+        .events
+        .iter()
+        .filter(|e| !e.starred_at_estimated)
+        .map(|e| e.starred_at)
+        .max()
+    {
+        conn.execute(
+            "UPDATE users SET last_starred_at = ?1 WHERE user_id = ?2 AND (
+                 last_starred_at IS NULL OR last_starred_at < ?1
+             )",
+            params![max_starred.to_rfc3339(), user_id],
         )?;
-        tx.commit()?;
-        Ok(inserted)
-    })
-    .await??;
+    }
 
-    let mut sorted_events = events.to_vec();
+    // Estimated times all equal the fetch time and would read as a burst of activity.
+    let mut sorted_events: Vec<_> = batch
+        .events
+        .iter()
+        .filter(|e| !e.starred_at_estimated)
+        .cloned()
+        .collect();
     sorted_events.sort_by_key(|e| e.starred_at);
     let gaps = compute_gap_minutes(&sorted_events, user.last_starred_at);
 
-    update_after_events(
-        db_path,
-        user,
-        None,
-        fetched_at,
-        etag,
-        last_modified,
-        config,
-        inserted_count,
-        &gaps,
-    )
-    .await
+    update_after_events(conn, batch, settings, None, inserted, &gaps)
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn update_after_events(
-    db_path: &Path,
-    user: &UserRecord,
+fn update_after_events(
+    conn: &Connection,
+    batch: &StarBatch,
+    settings: &WriteSettings,
     cached_last_starred: Option<DateTime<Utc>>,
-    fetched_at: DateTime<Utc>,
-    etag: Option<String>,
-    last_modified: Option<String>,
-    config: &Config,
     inserted_count: i64,
     gaps: &[i64],
-) -> Result<i64> {
-    let min_interval = config.min_interval_minutes;
-    let max_interval = config.max_interval_minutes;
-    let default_interval = config.default_interval_minutes;
-    let previous_interval = user.fetch_interval_minutes;
-    let previous_star_count = user.star_count;
-    let previous_ema = user.ema_minutes;
-    let new_star_count = previous_star_count + inserted_count;
-
-    let activity = recompute_interval(
-        db_path,
+) -> rusqlite::Result<i64> {
+    let user = &batch.user;
+    let new_star_count = user.star_count + inserted_count;
+
+    let activity = compute_activity(
+        conn,
         user.user_id,
-        min_interval,
-        max_interval,
-        default_interval,
-        previous_interval,
-        previous_star_count,
-        previous_ema,
+        settings.min_interval,
+        settings.max_interval,
+        settings.default_interval,
+        settings.ema_alpha,
+        &settings.activity_tiers,
+        user.fetch_interval_minutes,
+        user.star_count,
+        user.ema_minutes,
         new_star_count,
-        gaps.to_vec(),
-    )
-    .await?;
-    let next_check = next_check_with_jitter(fetched_at, activity.interval_minutes);
-    let next = next_check.to_rfc3339();
-    let fetched = fetched_at.to_rfc3339();
-    let etag_val = etag;
-    let last_mod_val = last_modified;
-    let activity_tier = activity.activity_tier.clone();
-    let ema_value = activity.ema_minutes;
-    let user_id = user.user_id;
-    let path = db_path.to_path_buf();
-    tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
-        let conn = Connection::open(path)?;
+        gaps,
+    )?;
+    // A pin overrides the schedule; the EMA keeps tracking so unpinning resumes it.
+    let interval_minutes = user
+        .pinned_interval_minutes
+        .unwrap_or(activity.interval_minutes);
+    let next_check = schedule_next_check(batch.fetched_at, interval_minutes, batch.poll_interval);
+    conn.execute(
+        "UPDATE users SET next_check_at = ?1, fetch_interval_minutes = ?2, last_fetched_at = ?3,
+         etag = COALESCE(?4, etag), last_modified = COALESCE(?5, last_modified), activity_tier = ?6,
+         ema_minutes = ?7, star_count = ?8, consecutive_failures = 0
+         WHERE user_id = ?9",
+        params![
+            next_check.to_rfc3339(),
+            interval_minutes,
+            batch.fetched_at.to_rfc3339(),
+            batch.etag,
+            batch.last_modified,
+            activity.activity_tier,
+            activity.ema_minutes,
+            new_star_count,
+            user.user_id
+        ],
+    )?;
+    if let Some(last_starred) = cached_last_starred {
         conn.execute(
-            "UPDATE users SET next_check_at = ?1, fetch_interval_minutes = ?2, last_fetched_at = ?3,
-             etag = COALESCE(?4, etag), last_modified = COALESCE(?5, last_modified), activity_tier = ?6,
-             ema_minutes = ?7, star_count = ?8
-             WHERE user_id = ?9",
-            params![
-                next,
-                activity.interval_minutes,
-                fetched,
-                etag_val,
-                last_mod_val,
-                activity_tier,
-                ema_value,
-                new_star_count,
-                user_id
-            ],
+            "UPDATE users SET last_starred_at = COALESCE(last_starred_at, ?1) WHERE user_id = ?2",
+            params![last_starred.to_rfc3339(), user.user_id],
         )?;
-        if let Some(last_starred) = cached_last_starred {
-            conn.execute(
-                "UPDATE users SET last_starred_at = COALESCE(last_starred_at, ?1) WHERE user_id = ?2",
-                params![last_starred.to_rfc3339(), user_id],
-            )?;
-        }
-        Ok(())
-    })
-    .await??;
-    Ok(activity.interval_minutes)
+    }
+    Ok(interval_minutes)
 }
 
 #[derive(Debug, Clone)]
@@ -413,6 +1254,8 @@ pub async fn recompute_interval(
     min_interval: i64,
     max_interval: i64,
     default_interval: i64,
+    ema_alpha: f64,
+    tiers: &ActivityTiers,
     previous_interval: i64,
     previous_star_count: i64,
     previous_ema: Option<f64>,
@@ -420,80 +1263,340 @@ pub async fn recompute_interval(
     gaps: Vec<i64>,
 ) -> Result<ActivityProfile> {
     let path = db_path.to_path_buf();
+    let tiers = tiers.clone();
     let profile = tokio::task::spawn_blocking(move || -> rusqlite::Result<ActivityProfile> {
-        let mut conn = Connection::open(path)?;
-        let min_clamped = min_interval.max(1);
-        let max_clamped = max_interval.max(min_clamped);
-        let fallback_default = default_interval.clamp(min_clamped, max_clamped);
-        let fallback_zero = max_clamped;
-        let mut interval_minutes = previous_interval.clamp(min_clamped, max_clamped);
-        let mut ema = previous_ema;
-        let alpha = 0.3f64;
-        let min_f = min_clamped as f64;
-        let max_f = max_clamped as f64;
-
-        let mut star_count = previous_star_count;
-        for gap in &gaps {
-            star_count += 1;
-            let gap_minutes = (*gap).max(1) as f64;
-
-            if star_count < 3 {
-                ema = None;
-                interval_minutes = fallback_default;
-                continue;
-            }
+        let conn = open_connection(&path)?;
+        compute_activity(
+            &conn,
+            user_id,
+            min_interval,
+            max_interval,
+            default_interval,
+            ema_alpha,
+            &tiers,
+            previous_interval,
+            previous_star_count,
+            previous_ema,
+            new_star_count,
+            &gaps,
+        )
+    })
+    .await??;
+    Ok(profile)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn compute_activity(
+    conn: &Connection,
+    user_id: i64,
+    min_interval: i64,
+    max_interval: i64,
+    default_interval: i64,
+    ema_alpha: f64,
+    tiers: &ActivityTiers,
+    previous_interval: i64,
+    previous_star_count: i64,
+    previous_ema: Option<f64>,
+    new_star_count: i64,
+    gaps: &[i64],
+) -> rusqlite::Result<ActivityProfile> {
+    let min_clamped = min_interval.max(1);
+    let max_clamped = max_interval.max(min_clamped);
+    let fallback_default = default_interval.clamp(min_clamped, max_clamped);
+    let fallback_zero = max_clamped;
+    let mut interval_minutes = previous_interval.clamp(min_clamped, max_clamped);
+    let mut ema = previous_ema;
+    let min_f = min_clamped as f64;
+    let max_f = max_clamped as f64;
+
+    let mut star_count = previous_star_count;
+    for gap in gaps {
+        star_count += 1;
+        let gap_minutes = (*gap).max(1) as f64;
+
+        if star_count < 3 {
+            ema = None;
+            interval_minutes = fallback_default;
+            continue;
+        }
+
+        if ema.is_none() {
+            let avg =
+                compute_average_gap_minutes(conn, user_id)?.unwrap_or(fallback_default as f64);
+            let clamped = avg.clamp(min_f, max_f);
+            ema = Some(clamped);
+        }
+
+        if let Some(current) = ema {
+            let mut new_ema = ema_alpha * gap_minutes + (1.0 - ema_alpha) * current;
+            new_ema = new_ema.clamp(min_f, max_f);
+            ema = Some(new_ema);
+            interval_minutes = new_ema.round() as i64;
+        }
+    }
+
+    star_count = new_star_count;
+    if star_count == 0 {
+        ema = None;
+        interval_minutes = fallback_zero;
+    } else if star_count < 3 {
+        ema = None;
+        interval_minutes = fallback_default;
+    } else if gaps.is_empty() {
+        if let Some(current) = ema {
+            interval_minutes = current.round() as i64;
+        } else if let Some(avg) = compute_average_gap_minutes(conn, user_id)? {
+            let clamped = avg.clamp(min_f, max_f);
+            ema = Some(clamped);
+            interval_minutes = clamped.round() as i64;
+        } else {
+            interval_minutes = fallback_default;
+        }
+    }
+
+    interval_minutes = interval_minutes.clamp(min_clamped, max_clamped);
+    let activity_tier = tiers.classify(interval_minutes).to_string();
+
+    Ok(ActivityProfile {
+        interval_minutes,
+        activity_tier: Some(activity_tier),
+        ema_minutes: ema,
+    })
+}
+
+/// Replays each user's stored star history through the EMA scheduler so interval
+/// and tier changes take effect without waiting for the next poll.
+pub async fn recompute_all_users(db_path: &Path, config: &Config) -> Result<usize> {
+    let users = all_users(db_path).await?;
+    let now = Utc::now();
+    for user in &users {
+        let path = db_path.to_path_buf();
+        let user_id = user.user_id;
+        let (star_count, gaps) =
+            tokio::task::spawn_blocking(move || -> rusqlite::Result<(i64, Vec<i64>)> {
+                let conn = open_connection(&path)?;
+                let mut stmt = conn.prepare(
+                    "SELECT starred_at FROM stars WHERE user_id = ?1 ORDER BY starred_at ASC",
+                )?;
+                let mut rows = stmt.query([user_id])?;
+                let mut count = 0i64;
+                let mut gaps = Vec::new();
+                let mut prev: Option<DateTime<Utc>> = None;
+                while let Some(row) = rows.next()? {
+                    let starred_at_str: String = row.get(0)?;
+                    let ts = parse_datetime_sql(&starred_at_str, 0)?;
+                    if let Some(prev_ts) = prev {
+                        let gap = (ts - prev_ts).num_minutes();
+                        if gap > 0 {
+                            gaps.push(gap);
+                        }
+                    }
+                    prev = Some(ts);
+                    count += 1;
+                }
+                Ok((count, gaps))
+            })
+            .await??;
 
-            if ema.is_none() {
-                let avg = compute_average_gap_minutes(&mut conn, user_id)?
-                    .unwrap_or(fallback_default as f64);
-                let clamped = avg.clamp(min_f, max_f);
-                ema = Some(clamped);
+        let activity = recompute_interval(
+            db_path,
+            user_id,
+            config.min_interval_minutes,
+            config.max_interval_minutes,
+            config.default_interval_minutes,
+            config.ema_alpha,
+            &config.activity_tiers,
+            config.default_interval_minutes,
+            star_count.min(1),
+            None,
+            star_count,
+            gaps,
+        )
+        .await?;
+
+        let interval_minutes = user
+            .pinned_interval_minutes
+            .unwrap_or(activity.interval_minutes);
+        let base = user.last_fetched_at.unwrap_or(now);
+        let next = next_check_with_jitter(base, interval_minutes).to_rfc3339();
+        let path = db_path.to_path_buf();
+        tokio::task::spawn_blocking(move || -> rusqlite::Result<()> {
+            let conn = open_connection(&path)?;
+            conn.execute(
+                "UPDATE users SET fetch_interval_minutes = ?1, activity_tier = ?2, ema_minutes = ?3,
+                 star_count = ?4, next_check_at = ?5
+                 WHERE user_id = ?6",
+                params![
+                    interval_minutes,
+                    activity.activity_tier,
+                    activity.ema_minutes,
+                    star_count,
+                    next,
+                    user_id
+                ],
+            )?;
+            Ok(())
+        })
+        .await??;
+    }
+    Ok(users.len())
+}
+
+/// Newest stars for a feed. `pushed_since` drops repositories last pushed
+/// before it; stars without a recorded push time are always kept.
+/// `max_per_user` keeps only each user's newest stars so one busy account
+/// cannot fill the feed.
+pub async fn recent_events_for_feed(
+    db_path: &Path,
+    limit: usize,
+    pushed_since: Option<DateTime<Utc>>,
+    max_per_user: Option<usize>,
+) -> Result<Vec<StarFeedRow>> {
+    use crate::db::star_query::{self, StarQuery};
+
+    let query = StarQuery {
+        pushed_since,
+        max_per_user,
+        page: 1,
+        page_size: limit.max(1),
+        ..StarQuery::default()
+    };
+    let result = star_query::query_stars(db_path, &query).await?;
+    Ok(result.items)
+}
+
+/// A named band of polling intervals: users polled at most every
+/// `max_interval_minutes` minutes, or any slower, for the open-ended tier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityTier {
+    pub name: String,
+    pub max_interval_minutes: Option<i64>,
+}
+
+impl std::fmt::Display for ActivityTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max_interval_minutes {
+            Some(max) => write!(f, "{}={max}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Activity tiers assigned by `recompute_interval`, fastest first. The last
+/// tier is the only open-ended one, so every interval has exactly one tier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityTiers(Vec<ActivityTier>);
+
+impl Default for ActivityTiers {
+    fn default() -> Self {
+        let tier = |name: &str, max: Option<i64>| ActivityTier {
+            name: name.to_string(),
+            max_interval_minutes: max,
+        };
+        Self(vec![
+            tier("high", Some(60)),
+            tier("medium", Some(24 * 60)),
+            tier("low", None),
+        ])
+    }
+}
+
+impl ActivityTiers {
+    /// Orders `tiers` by bound and checks that names and bounds are distinct,
+    /// and that exactly one tier is open-ended.
+    pub fn new(mut tiers: Vec<ActivityTier>) -> Result<Self> {
+        let open_ended = tiers
+            .iter()
+            .filter(|tier| tier.max_interval_minutes.is_none())
+            .count();
+        if open_ended != 1 {
+            return Err(anyhow!(
+                "exactly one activity tier must omit its upper bound, found {open_ended}"
+            ));
+        }
+        tiers.sort_by_key(|tier| tier.max_interval_minutes.unwrap_or(i64::MAX));
+        for pair in tiers.windows(2) {
+            if pair[0].max_interval_minutes == pair[1].max_interval_minutes {
+                return Err(anyhow!(
+                    "activity tiers '{}' and '{}' share an upper bound",
+                    pair[0].name,
+                    pair[1].name
+                ));
             }
+        }
+        for (index, tier) in tiers.iter().enumerate() {
+            if tiers[..index].iter().any(|seen| seen.name == tier.name) {
+                return Err(anyhow!("activity tier '{}' is defined twice", tier.name));
+            }
+        }
+        Ok(Self(tiers))
+    }
+
+    /// The first tier whose bound covers `interval_minutes`.
+    pub fn classify(&self, interval_minutes: i64) -> &str {
+        self.0
+            .iter()
+            .find(|tier| {
+                tier.max_interval_minutes
+                    .is_none_or(|max| interval_minutes <= max)
+            })
+            .map(|tier| tier.name.as_str())
+            .unwrap_or("unknown")
+    }
+
+    /// The open-ended tier, used for users parked after a 404.
+    pub fn slowest(&self) -> &str {
+        self.0
+            .last()
+            .map(|tier| tier.name.as_str())
+            .unwrap_or("low")
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|tier| tier.name == name)
+    }
 
-            if let Some(current) = ema {
-                let mut new_ema = alpha * gap_minutes + (1.0 - alpha) * current;
-                new_ema = new_ema.clamp(min_f, max_f);
-                ema = Some(new_ema);
-                interval_minutes = new_ema.round() as i64;
-            }
-        }
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|tier| tier.name.as_str())
+    }
 
-        star_count = new_star_count;
-        if star_count == 0 {
-            ema = None;
-            interval_minutes = fallback_zero;
-        } else if star_count < 3 {
-            ema = None;
-            interval_minutes = fallback_default;
-        } else if gaps.is_empty() {
-            if let Some(current) = ema {
-                interval_minutes = current.round() as i64;
-            } else if let Some(avg) = compute_average_gap_minutes(&mut conn, user_id)? {
-                let clamped = avg.clamp(min_f, max_f);
-                ema = Some(clamped);
-                interval_minutes = clamped.round() as i64;
-            } else {
-                interval_minutes = fallback_default;
-            }
-        }
+    pub fn iter(&self) -> impl Iterator<Item = &ActivityTier> {
+        self.0.iter()
+    }
+}
 
-        interval_minutes = interval_minutes.clamp(min_clamped, max_clamped);
-        let activity_tier = derive_activity_tier(interval_minutes);
+pub async fn recent_events_by_tier(
+    db_path: &Path,
+    tier: &str,
+    limit: usize,
+    pushed_since: Option<DateTime<Utc>>,
+) -> Result<Vec<StarFeedRow>> {
+    use crate::db::star_query::{self, StarQuery};
 
-        Ok(ActivityProfile {
-            interval_minutes,
-            activity_tier: Some(activity_tier),
-            ema_minutes: ema,
-        })
-    })
-    .await??;
-    Ok(profile)
+    let query = StarQuery {
+        activity: Some(tier.to_string()),
+        pushed_since,
+        page: 1,
+        page_size: limit.max(1),
+        ..StarQuery::default()
+    };
+    let result = star_query::query_stars(db_path, &query).await?;
+    Ok(result.items)
 }
 
-pub async fn recent_events_for_feed(db_path: &Path, limit: usize) -> Result<Vec<StarFeedRow>> {
+/// Newest stars on repositories in `language`, matched case-insensitively and
+/// with the same aliases as the `language` filter.
+pub async fn recent_events_by_language(
+    db_path: &Path,
+    language: &str,
+    limit: usize,
+    pushed_since: Option<DateTime<Utc>>,
+) -> Result<Vec<StarFeedRow>> {
     use crate::db::star_query::{self, StarQuery};
 
     let query = StarQuery {
+        language: Some(language.to_string()),
+        pushed_since,
         page: 1,
         page_size: limit.max(1),
         ..StarQuery::default()
@@ -510,6 +1613,7 @@ pub struct StarFeedRow {
     pub repo_language: Option<String>,
     pub repo_topics: Vec<String>,
     pub repo_html_url: String,
+    pub repo_owner_avatar_url: Option<String>,
     pub starred_at: DateTime<Utc>,
     pub fetched_at: DateTime<Utc>,
     pub user_activity_tier: Option<String>,
@@ -543,6 +1647,19 @@ fn parse_topics(value: Option<String>) -> rusqlite::Result<Vec<String>> {
     }
 }
 
+/// Jittered next check, floored at GitHub's requested poll spacing when it sent one.
+fn schedule_next_check(
+    base: DateTime<Utc>,
+    interval_minutes: i64,
+    poll_interval: Option<std::time::Duration>,
+) -> DateTime<Utc> {
+    let next = next_check_with_jitter(base, interval_minutes);
+    match poll_interval.and_then(|wait| Duration::from_std(wait).ok()) {
+        Some(floor) => next.max(base + floor),
+        None => next,
+    }
+}
+
 fn next_check_with_jitter(base: DateTime<Utc>, interval_minutes: i64) -> DateTime<Utc> {
     if interval_minutes <= 0 {
         return base + Duration::minutes(1);
@@ -561,14 +1678,6 @@ fn next_check_with_jitter(base: DateTime<Utc>, interval_minutes: i64) -> DateTim
     base + Duration::minutes(total_minutes)
 }
 
-fn derive_activity_tier(interval_minutes: i64) -> String {
-    match interval_minutes {
-        n if n <= 60 => "high".to_string(),
-        n if n <= 24 * 60 => "medium".to_string(),
-        _ => "low".to_string(),
-    }
-}
-
 fn compute_gap_minutes(
     events: &[StarEvent],
     previous_last_starred: Option<DateTime<Utc>>,
@@ -587,10 +1696,7 @@ fn compute_gap_minutes(
     gaps
 }
 
-fn compute_average_gap_minutes(
-    conn: &mut Connection,
-    user_id: i64,
-) -> rusqlite::Result<Option<f64>> {
+fn compute_average_gap_minutes(conn: &Connection, user_id: i64) -> rusqlite::Result<Option<f64>> {
     let mut stmt =
         conn.prepare("SELECT starred_at FROM stars WHERE user_id = ?1 ORDER BY starred_at ASC")?;
     let mut rows = stmt.query([user_id])?;
@@ -649,6 +1755,220 @@ mod tests {
     use chrono::{TimeZone, Utc};
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn open_connection_applies_cache_pragma() {
+        let temp = NamedTempFile::new().unwrap();
+        let conn = open_connection(temp.path()).unwrap();
+        let cache_size: i64 = conn
+            .pragma_query_value(None, "cache_size", |row| row.get(0))
+            .unwrap();
+        assert_eq!(cache_size, -(DEFAULT_SQLITE_CACHE_KB as i64));
+        let synchronous: i64 = conn
+            .pragma_query_value(None, "synchronous", |row| row.get(0))
+            .unwrap();
+        assert_eq!(synchronous, 1, "NORMAL");
+        let journal_mode: String = conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+    }
+
+    #[tokio::test]
+    async fn checkpoint_truncates_wal() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        upsert_followings(
+            temp.path(),
+            &[FollowingUser {
+                id: 1,
+                login: "alice".into(),
+            }],
+            60,
+            "low",
+        )
+        .await
+        .unwrap();
+
+        let checkpoint = checkpoint_wal(temp.path()).await.unwrap();
+        assert!(!checkpoint.busy);
+        assert_eq!(checkpoint.log_frames, checkpoint.checkpointed_frames);
+        let wal = temp.path().with_file_name(format!(
+            "{}-wal",
+            temp.path().file_name().unwrap().to_string_lossy()
+        ));
+        assert_eq!(std::fs::metadata(wal).map(|m| m.len()).unwrap_or(0), 0);
+    }
+
+    #[tokio::test]
+    async fn new_followings_start_in_the_slowest_configured_tier() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let tiers = ActivityTiers::new(vec![
+            ActivityTier {
+                name: "hot".into(),
+                max_interval_minutes: Some(60),
+            },
+            ActivityTier {
+                name: "dormant".into(),
+                max_interval_minutes: None,
+            },
+        ])
+        .unwrap();
+        upsert_followings(
+            temp.path(),
+            &[FollowingUser {
+                id: 1,
+                login: "alice".into(),
+            }],
+            60,
+            tiers.slowest(),
+        )
+        .await
+        .unwrap();
+
+        let users = all_users(temp.path()).await.unwrap();
+        assert_eq!(users[0].activity_tier.as_deref(), Some("dormant"));
+    }
+
+    #[tokio::test]
+    async fn database_stats_count_rows_and_sidecar_files() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        upsert_followings(
+            temp.path(),
+            &[
+                FollowingUser {
+                    id: 1,
+                    login: "alice".into(),
+                },
+                FollowingUser {
+                    id: 2,
+                    login: "bob".into(),
+                },
+            ],
+            60,
+            "low",
+        )
+        .await
+        .unwrap();
+
+        let stats = database_stats(temp.path()).await.unwrap();
+        assert_eq!(stats.total_users, 2);
+        assert_eq!(stats.total_stars, 0);
+        let main_file = std::fs::metadata(temp.path()).unwrap().len();
+        assert!(stats.size_bytes >= main_file);
+        assert!(main_file > 0);
+    }
+
+    #[tokio::test]
+    async fn maintenance_reclaims_free_pages() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        {
+            let conn = Connection::open(temp.path()).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE scratch (blob TEXT);
+                 WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+                 INSERT INTO scratch SELECT hex(randomblob(2048)) FROM n;
+                 DROP TABLE scratch;",
+            )
+            .unwrap();
+        }
+
+        let report = run_maintenance(temp.path()).await.unwrap();
+        assert!(report.freed_bytes() > 0);
+        assert!(!report.checkpoint.busy);
+        let analyzed: i64 = Connection::open(temp.path())
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'sqlite_stat1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(analyzed, 1);
+    }
+
+    #[tokio::test]
+    async fn maintenance_drops_watermarks_past_the_cookie_lifetime() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now();
+        let expired = now - chrono::Duration::days(WATERMARK_RETENTION_DAYS + 1);
+        store_watermark(temp.path(), "old", Some(1), expired)
+            .await
+            .unwrap();
+        store_watermark(temp.path(), "recent", Some(2), now)
+            .await
+            .unwrap();
+
+        let report = run_maintenance(temp.path()).await.unwrap();
+        assert_eq!(report.watermarks_pruned, 1);
+        assert_eq!(watermark(temp.path(), "old").await.unwrap(), None);
+        assert_eq!(watermark(temp.path(), "recent").await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn storing_a_watermark_evicts_the_least_recently_updated() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let long_ago = Utc::now() - chrono::Duration::days(30);
+        Connection::open(temp.path())
+            .unwrap()
+            .execute(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+                 INSERT INTO client_watermarks (client_id, seen_sequence, updated_at)
+                 SELECT 'client-' || i, i, ?2 FROM n",
+                params![MAX_CLIENT_WATERMARKS, long_ago.to_rfc3339()],
+            )
+            .unwrap();
+        store_watermark(temp.path(), "client-1", Some(7), Utc::now())
+            .await
+            .unwrap();
+        store_watermark(temp.path(), "newcomer", Some(9), Utc::now())
+            .await
+            .unwrap();
+
+        let count: i64 = Connection::open(temp.path())
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM client_watermarks", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, MAX_CLIENT_WATERMARKS);
+        assert_eq!(watermark(temp.path(), "client-1").await.unwrap(), Some(7));
+        assert_eq!(watermark(temp.path(), "newcomer").await.unwrap(), Some(9));
+    }
+
+    #[tokio::test]
+    async fn init_indexes_language_for_option_counts() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let conn = Connection::open(temp.path()).unwrap();
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_stars_language'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 1);
+        let plan = conn
+            .prepare(
+                "EXPLAIN QUERY PLAN SELECT repo_language, COUNT(*) FROM stars
+                 WHERE repo_language IS NOT NULL AND repo_language != ''
+                 GROUP BY repo_language",
+            )
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap()
+            .join("\n");
+        assert!(plan.contains("idx_stars_language"), "{plan}");
+    }
+
     #[tokio::test]
     async fn ema_fallback_for_sparse_history() {
         let temp = NamedTempFile::new().unwrap();
@@ -660,6 +1980,8 @@ mod tests {
             10,
             7 * 24 * 60,
             60,
+            0.3,
+            &ActivityTiers::default(),
             60,
             1,
             None,
@@ -685,6 +2007,8 @@ mod tests {
             10,
             7 * 24 * 60,
             60,
+            0.3,
+            &ActivityTiers::default(),
             90,
             3,
             Some(90.0),
@@ -699,6 +2023,35 @@ mod tests {
         assert_eq!(profile.ema_minutes.unwrap(), 72.0);
     }
 
+    #[tokio::test]
+    async fn higher_ema_alpha_reacts_faster() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let mut intervals = Vec::new();
+        for alpha in [0.3, 0.8] {
+            let profile = recompute_interval(
+                temp.path(),
+                1,
+                10,
+                7 * 24 * 60,
+                60,
+                alpha,
+                &ActivityTiers::default(),
+                90,
+                3,
+                Some(90.0),
+                4,
+                vec![30],
+            )
+            .await
+            .unwrap();
+            intervals.push(profile.interval_minutes);
+        }
+
+        assert_eq!(intervals, vec![72, 42]);
+    }
+
     #[tokio::test]
     async fn ema_bootstrap_on_third_event() {
         let temp = NamedTempFile::new().unwrap();
@@ -733,6 +2086,8 @@ mod tests {
             10,
             7 * 24 * 60,
             60,
+            0.3,
+            &ActivityTiers::default(),
             60,
             2,
             None,
@@ -758,6 +2113,8 @@ mod tests {
             10,
             7 * 24 * 60,
             60,
+            0.3,
+            &ActivityTiers::default(),
             60,
             0,
             None,
@@ -772,6 +2129,359 @@ mod tests {
         assert!(profile.ema_minutes.is_none());
     }
 
+    #[tokio::test]
+    async fn recompute_all_users_applies_new_bounds() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count)
+             VALUES (?1, ?2, ?3, ?4, 'low', NULL, 0)",
+            params![1, "alice", 10080, Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+        let start = Utc.with_ymd_and_hms(2025, 10, 20, 0, 0, 0).unwrap();
+        for hour in 0..5 {
+            let ts = start + Duration::hours(hour);
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, NULL, NULL, ?3, ?4, ?4)",
+                params![1, format!("example/repo{hour}"), "https://example.com", ts.to_rfc3339()],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        let config = Config {
+            github_token: String::new(),
+            db_path: temp.path().to_path_buf(),
+            max_concurrency: 1,
+            rate_limit_pace_below: 0,
+            rate_limit_reserve: 0,
+            feed_length: 10,
+            html_length: 10,
+            default_interval_minutes: 60,
+            min_interval_minutes: 10,
+            max_interval_minutes: 24 * 60,
+            ema_alpha: 0.3,
+            activity_tiers: Default::default(),
+            api_base_url: url::Url::parse("https://api.github.com").unwrap(),
+            user_agent: "test".into(),
+            contact: None,
+            timeout_secs: 5,
+            connect_timeout_secs: 10,
+            max_response_bytes: 32 * 1024 * 1024,
+            no_conditional: false,
+            proxy: Default::default(),
+            repo_filter: Default::default(),
+            include_self: false,
+            keep_unfollowed: false,
+            extra_users: Vec::new(),
+            extra_orgs: Vec::new(),
+            interval_overrides: Vec::new(),
+            feed_enclosures: false,
+            feed_order: Default::default(),
+            max_repo_idle_days: None,
+            max_items_per_user: None,
+            feed_warn_threshold: 100,
+            feed_hard_max: 1000,
+            backfill: false,
+            backfill_ascending: false,
+            backfill_since_days: None,
+            poll_deadline_secs: None,
+            max_pages_per_user: None,
+            sqlite: Default::default(),
+            mode: crate::config::Mode::Recompute,
+        };
+        let updated = recompute_all_users(temp.path(), &config).await.unwrap();
+        assert_eq!(updated, 1);
+
+        let users = all_users(temp.path()).await.unwrap();
+        assert_eq!(users[0].fetch_interval_minutes, 60);
+        assert_eq!(users[0].activity_tier.as_deref(), Some("high"));
+        assert_eq!(users[0].star_count, 5);
+    }
+
+    #[tokio::test]
+    async fn interval_pins_override_the_adaptive_schedule() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let later = Utc::now() + Duration::days(7);
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count)
+             VALUES (?1, ?2, ?3, ?4, 'low', NULL, 0)",
+            params![1, "Alice", 10080, later.to_rfc3339()],
+        )
+        .unwrap();
+        drop(conn);
+
+        let now = Utc::now();
+        let pins = [IntervalOverride {
+            login: "alice".into(),
+            interval_minutes: 5,
+        }];
+        apply_interval_pins(temp.path(), &pins, now).await.unwrap();
+        let due = due_users(temp.path(), now + Duration::minutes(10))
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].pinned_interval_minutes, Some(5));
+
+        let settings = WriteSettings {
+            min_interval: 10,
+            max_interval: 10080,
+            default_interval: 60,
+            ema_alpha: 0.3,
+            activity_tiers: ActivityTiers::default(),
+            repo_filter: Default::default(),
+        };
+        let conn = open_connection(temp.path()).unwrap();
+        let batch = StarBatch {
+            user: due[0].clone(),
+            events: Vec::new(),
+            fetched_at: now,
+            etag: None,
+            last_modified: None,
+            poll_interval: None,
+        };
+        // Zero stars would otherwise fall back to the maximum interval.
+        assert_eq!(write_star_batch(&conn, &batch, &settings).unwrap(), 5);
+        drop(conn);
+
+        apply_interval_pins(temp.path(), &[], now).await.unwrap();
+        let users = all_users(temp.path()).await.unwrap();
+        assert_eq!(users[0].pinned_interval_minutes, None);
+    }
+
+    #[test]
+    fn github_poll_interval_floors_the_next_check() {
+        let base = Utc.with_ymd_and_hms(2025, 10, 20, 0, 0, 0).unwrap();
+        let floor = Some(std::time::Duration::from_secs(2 * 60 * 60));
+        for _ in 0..20 {
+            assert!(schedule_next_check(base, 10, floor) >= base + Duration::hours(2));
+            assert!(schedule_next_check(base, 10, None) <= base + Duration::minutes(11));
+        }
+        let relaxed = schedule_next_check(base, 600, Some(std::time::Duration::from_secs(60)));
+        assert!(relaxed >= base + Duration::minutes(570));
+    }
+
+    #[tokio::test]
+    async fn reset_validators_forces_a_full_fetch() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let later = Utc::now() + Duration::days(1);
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, etag, last_modified, fetch_interval_minutes, next_check_at)
+             VALUES (1, 'alice', '\"stale\"', 'Sat, 18 Oct 2025 04:15:00 GMT', 60, ?1)",
+            params![later.to_rfc3339()],
+        )
+        .unwrap();
+        drop(conn);
+
+        let now = Utc::now();
+        assert!(reset_validators(temp.path(), "ALICE", now).await.unwrap());
+        assert!(!reset_validators(temp.path(), "ghost", now).await.unwrap());
+
+        let due = due_users(temp.path(), now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(due[0].etag.is_none());
+        assert!(due[0].last_modified.is_none());
+    }
+
+    #[tokio::test]
+    async fn import_matches_users_by_login_and_skips_known_stars() {
+        let ours = NamedTempFile::new().unwrap();
+        let theirs = NamedTempFile::new().unwrap();
+        init(ours.path()).await.unwrap();
+        init(theirs.path()).await.unwrap();
+        let star = |conn: &Connection, user_id: i64, repo: &str, day: u32| {
+            let at = format!("2025-01-{day:02}T00:00:00+00:00");
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, '[\"cli\"]', 'https://example.com', ?3, ?3)",
+                params![user_id, repo, at],
+            )
+            .unwrap();
+        };
+
+        let conn = Connection::open(ours.path()).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (user_id, login, last_starred_at, fetch_interval_minutes, next_check_at)
+             VALUES (1, 'alice', '2025-01-02T00:00:00+00:00', 60, '2025-01-01T00:00:00+00:00'),
+                    (9, 'carol', '2025-01-02T00:00:00+00:00', 60, '2025-01-01T00:00:00+00:00');
+             INSERT INTO hidden_events (user_id, repo_full_name, hidden_at)
+             VALUES (1, 'acme/noise', '2025-01-01T00:00:00+00:00');",
+        )
+        .unwrap();
+        star(&conn, 1, "acme/shared", 2);
+        star(&conn, 9, "acme/carols", 2);
+        drop(conn);
+
+        // Alice has another id there; bob is new, and carol's star is hidden there.
+        let conn = Connection::open(theirs.path()).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (user_id, login, last_starred_at, fetch_interval_minutes, next_check_at, display_name)
+             VALUES (7, 'Alice', '2025-01-05T00:00:00+00:00', 60, '2025-01-01T00:00:00+00:00', 'Alice A.'),
+                    (8, 'bob', '2025-01-03T00:00:00+00:00', 30, '2025-01-01T00:00:00+00:00', NULL),
+                    (9, 'carol', '2025-01-02T00:00:00+00:00', 60, '2025-01-01T00:00:00+00:00', NULL);
+             INSERT INTO hidden_events (user_id, repo_full_name, hidden_at)
+             VALUES (9, 'acme/carols', '2025-01-03T00:00:00+00:00');",
+        )
+        .unwrap();
+        star(&conn, 7, "acme/shared", 2);
+        star(&conn, 7, "acme/newer", 5);
+        star(&conn, 7, "acme/noise", 4);
+        star(&conn, 8, "acme/bobs", 3);
+        drop(conn);
+
+        let report = import_database(ours.path(), theirs.path()).await.unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                users_added: 1,
+                users_merged: 2,
+                stars_added: 2,
+            }
+        );
+        let alice = user_by_login(ours.path(), "alice").await.unwrap().unwrap();
+        assert_eq!(alice.user_id, 1);
+        assert_eq!(alice.star_count, 2);
+        assert_eq!(
+            alice.last_starred_at.unwrap().to_rfc3339(),
+            "2025-01-05T00:00:00+00:00"
+        );
+        let bob = user_by_login(ours.path(), "bob").await.unwrap().unwrap();
+        assert_eq!((bob.user_id, bob.star_count), (8, 1));
+        // Hidden there means hidden here: carol's local star is gone for good.
+        let carol = user_by_login(ours.path(), "carol").await.unwrap().unwrap();
+        assert_eq!(carol.star_count, 0);
+        let hidden: i64 = Connection::open(ours.path())
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM hidden_events WHERE user_id = 9 AND repo_full_name = 'acme/carols'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hidden, 1);
+
+        let conn = Connection::open(ours.path()).unwrap();
+        let (name, topics): (String, i64) = conn
+            .query_row(
+                "SELECT display_name, (SELECT COUNT(*) FROM repo_topics) FROM users WHERE user_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "Alice A.");
+        assert_eq!(topics, 3);
+
+        // Importing again finds nothing new.
+        let again = import_database(ours.path(), theirs.path()).await.unwrap();
+        assert_eq!(again.stars_added, 0);
+        assert!(import_database(ours.path(), ours.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn not_found_parks_user_and_success_resets_streak() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at, activity_tier, ema_minutes, star_count)
+             VALUES (?1, ?2, ?3, ?4, 'high', NULL, 0)",
+            params![1, "ghost", 30, Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+        drop(conn);
+
+        assert_eq!(
+            record_user_not_found(temp.path(), 1, 1440, "low")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            record_user_not_found(temp.path(), 1, 1440, "low")
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(due_users(temp.path(), Utc::now()).await.unwrap().is_empty());
+
+        record_not_modified(temp.path(), 1, Utc::now(), 30, None)
+            .await
+            .unwrap();
+        let users = all_users(temp.path()).await.unwrap();
+        assert_eq!(users[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn hidden_stars_are_not_stored_again() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let starred = Utc.with_ymd_and_hms(2025, 10, 20, 0, 0, 0).unwrap();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at)
+             VALUES (1, 'alice', 60, ?1)",
+            params![Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO stars (id, user_id, repo_full_name, repo_html_url, starred_at, fetched_at)
+             VALUES (7, 1, 'example/noise', 'https://example.com', ?1, ?1)",
+            params![starred.to_rfc3339()],
+        )
+        .unwrap();
+        drop(conn);
+
+        assert!(hide_star(temp.path(), 7, Utc::now()).await.unwrap());
+        assert!(!hide_star(temp.path(), 7, Utc::now()).await.unwrap());
+
+        let event = |repo: &str| StarEvent {
+            repo_full_name: repo.to_string(),
+            repo_description: None,
+            repo_html_url: format!("https://github.com/{repo}"),
+            starred_at: starred,
+            repo_language: None,
+            repo_topics: Vec::new(),
+            repo_owner_avatar_url: None,
+            repo_pushed_at: None,
+            starred_at_estimated: false,
+        };
+        let batch = StarBatch {
+            user: all_users(temp.path()).await.unwrap().remove(0),
+            events: vec![event("example/noise"), event("example/signal")],
+            fetched_at: Utc::now(),
+            etag: None,
+            last_modified: None,
+            poll_interval: None,
+        };
+        let settings = WriteSettings {
+            min_interval: 10,
+            max_interval: 24 * 60,
+            default_interval: 60,
+            ema_alpha: 0.3,
+            activity_tiers: Default::default(),
+            repo_filter: Default::default(),
+        };
+        let conn = open_connection(temp.path()).unwrap();
+        write_star_batch(&conn, &batch, &settings).unwrap();
+        let repos: Vec<String> = conn
+            .prepare("SELECT repo_full_name FROM stars")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(repos, ["example/signal"]);
+    }
+
     #[test]
     fn jitter_respects_bounds() {
         let base = Utc::now();
@@ -791,10 +2501,91 @@ mod tests {
 
     #[test]
     fn activity_tier_thresholds() {
-        assert_eq!(derive_activity_tier(10), "high");
-        assert_eq!(derive_activity_tier(60), "high");
-        assert_eq!(derive_activity_tier(61), "medium");
-        assert_eq!(derive_activity_tier(1440), "medium");
-        assert_eq!(derive_activity_tier(1441), "low");
+        let tiers = ActivityTiers::default();
+        assert_eq!(tiers.classify(10), "high");
+        assert_eq!(tiers.classify(60), "high");
+        assert_eq!(tiers.classify(61), "medium");
+        assert_eq!(tiers.classify(1440), "medium");
+        assert_eq!(tiers.classify(1441), "low");
+    }
+
+    fn custom_tiers() -> ActivityTiers {
+        let tier = |name: &str, max: Option<i64>| ActivityTier {
+            name: name.to_string(),
+            max_interval_minutes: max,
+        };
+        ActivityTiers::new(vec![
+            tier("dormant", None),
+            tier("low", Some(7 * 24 * 60)),
+            tier("very-high", Some(15)),
+            tier("medium", Some(1440)),
+            tier("high", Some(60)),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn custom_tiers_are_ordered_by_bound() {
+        let tiers = custom_tiers();
+        assert_eq!(
+            tiers.names().collect::<Vec<_>>(),
+            ["very-high", "high", "medium", "low", "dormant"]
+        );
+        assert_eq!(tiers.classify(15), "very-high");
+        assert_eq!(tiers.classify(16), "high");
+        assert_eq!(tiers.classify(7 * 24 * 60), "low");
+        assert_eq!(tiers.classify(7 * 24 * 60 + 1), "dormant");
+        assert_eq!(tiers.slowest(), "dormant");
+
+        let tier = |name: &str, max: Option<i64>| ActivityTier {
+            name: name.to_string(),
+            max_interval_minutes: max,
+        };
+        assert!(ActivityTiers::new(vec![tier("high", Some(60))]).is_err());
+        assert!(ActivityTiers::new(vec![tier("a", None), tier("b", None)]).is_err());
+        assert!(
+            ActivityTiers::new(vec![
+                tier("a", Some(60)),
+                tier("b", Some(60)),
+                tier("c", None)
+            ])
+            .is_err()
+        );
+        assert!(ActivityTiers::new(vec![tier("a", Some(60)), tier("a", None)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn init_reclassifies_users_outside_the_configured_tiers() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now().to_rfc3339();
+        let conn = Connection::open(temp.path()).unwrap();
+        for (user_id, login, interval, tier) in [
+            (1, "alice", 10, Some("high")),
+            (2, "bob", 20_000, Some("sleepy")),
+            (3, "carol", 600, None),
+        ] {
+            conn.execute(
+                "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at, activity_tier)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![user_id, login, interval, now, tier],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        init_with_tiers(temp.path(), &custom_tiers()).await.unwrap();
+        let users = all_users(temp.path()).await.unwrap();
+        let tier_of = |login: &str| {
+            users
+                .iter()
+                .find(|user| user.login == login)
+                .and_then(|user| user.activity_tier.clone())
+        };
+        // "high" is still configured, so alice keeps it despite her interval.
+        assert_eq!(tier_of("alice").as_deref(), Some("high"));
+        // "sleepy" is no longer configured, so bob is classified by interval.
+        assert_eq!(tier_of("bob").as_deref(), Some("dormant"));
+        assert_eq!(tier_of("carol").as_deref(), Some("medium"));
     }
 }
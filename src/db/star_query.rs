@@ -2,23 +2,115 @@ use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use rusqlite::types::Value;
-use rusqlite::{Connection, OptionalExtension, params_from_iter};
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
 
-use super::{StarFeedRow, parse_datetime_sql, parse_topics};
+use super::{StarFeedRow, open_connection, parse_datetime_sql, parse_topics};
+
+const STAR_COLUMNS: &str = "u.login, s.repo_full_name, s.repo_description, s.repo_language, s.repo_topics, s.repo_html_url, s.starred_at, s.fetched_at, u.activity_tier, s.id, s.repo_owner_avatar_url";
+
+/// Canonical GitHub language names and the lowercase spellings that should
+/// resolve to them. Languages missing from this table pass through unchanged.
+const LANGUAGE_ALIASES: &[(&str, &[&str])] = &[
+    ("C#", &["csharp", "c sharp", "cs"]),
+    ("C++", &["cpp", "cplusplus", "c plus plus", "cxx"]),
+    ("Dockerfile", &["docker"]),
+    ("Emacs Lisp", &["elisp", "emacs-lisp"]),
+    ("F#", &["fsharp", "f sharp"]),
+    ("Go", &["golang"]),
+    ("JavaScript", &["js", "node", "nodejs"]),
+    ("Jupyter Notebook", &["jupyter", "ipynb", "notebook"]),
+    ("Kotlin", &["kt"]),
+    (
+        "Objective-C",
+        &["objc", "objective c", "objectivec", "obj-c"],
+    ),
+    ("PowerShell", &["pwsh", "ps1"]),
+    ("Python", &["py", "python3"]),
+    ("Ruby", &["rb"]),
+    ("Rust", &["rs"]),
+    ("Shell", &["sh", "bash", "shell script"]),
+    ("TypeScript", &["ts"]),
+    ("Vim Script", &["vimscript", "viml", "vim"]),
+];
+
+fn language_entry(value: &str) -> Option<&'static (&'static str, &'static [&'static str])> {
+    let needle = value.trim().to_lowercase();
+    LANGUAGE_ALIASES.iter().find(|(canonical, aliases)| {
+        canonical.to_lowercase() == needle || aliases.contains(&needle.as_str())
+    })
+}
+
+/// Resolves a language name or alias (`cpp`, `c++`, `CPlusPlus`) to its
+/// canonical GitHub spelling.
+pub fn canonical_language(value: &str) -> String {
+    match language_entry(value) {
+        Some((canonical, _)) => (*canonical).to_string(),
+        None => value.trim().to_string(),
+    }
+}
+
+/// Stable key for a repository whatever casing a star recorded its name in;
+/// GitHub treats `Owner/Repo` and `owner/repo` as the same repository.
+pub fn repo_identity(repo_full_name: &str) -> String {
+    repo_full_name.trim().to_ascii_lowercase()
+}
+
+/// SQL form of [`repo_identity`] over `stars s`. Names are stored trimmed and
+/// ASCII, so SQLite's ASCII-only `lower()` agrees with it.
+const REPO_IDENTITY_SQL: &str = "lower(s.repo_full_name)";
+
+/// Lowercase stored values that a language filter should match.
+fn language_match_values(value: &str) -> Vec<String> {
+    match language_entry(value) {
+        Some((canonical, aliases)) => std::iter::once(canonical.to_lowercase())
+            .chain(aliases.iter().map(|alias| (*alias).to_string()))
+            .collect(),
+        None => vec![value.trim().to_lowercase()],
+    }
+}
+
+/// Folds language counts onto canonical names, summing entries that alias the
+/// same language.
+fn merge_language_stats(stats: Vec<LanguageStat>) -> Vec<LanguageStat> {
+    let mut merged: Vec<LanguageStat> = Vec::with_capacity(stats.len());
+    for stat in stats {
+        let name = canonical_language(&stat.name);
+        match merged.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => existing.count += stat.count,
+            None => merged.push(LanguageStat {
+                name,
+                count: stat.count,
+            }),
+        }
+    }
+    merged.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    merged
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StarSort {
+    /// Most recently ingested first (`fetched_at`).
     Newest,
+    /// Most recently starred on GitHub first (`starred_at`).
+    StarredAt,
+    /// By repository name, case-insensitively. Ties fall back to newest
+    /// `fetched_at`, then login, then id, so the order is total and pages never
+    /// interleave when several users starred the same repository.
     Alpha,
+    /// Shuffled. A seed gives a stable order that pages consistently; without
+    /// one every request is reshuffled.
+    Random(Option<u32>),
 }
 
 impl StarSort {
     pub fn as_str(self) -> &'static str {
         match self {
             StarSort::Newest => "newest",
+            StarSort::StarredAt => "starred",
             StarSort::Alpha => "alpha",
+            StarSort::Random(_) => "random",
         }
     }
 }
@@ -44,12 +136,22 @@ impl UserFilterMode {
 pub struct StarQuery {
     pub search: Option<String>,
     pub language: Option<String>,
+    /// Matches stars whose repository carries this topic (case-insensitive).
+    pub topic: Option<String>,
     pub activity: Option<String>,
     pub user: Option<String>,
     pub user_mode: UserFilterMode,
     pub sort: StarSort,
+    pub since_sequence: Option<i64>,
+    /// Only stars on repositories pushed at or after this time; a NULL
+    /// `repo_pushed_at` always matches.
+    pub pushed_since: Option<DateTime<Utc>>,
+    /// Keep only each user's newest `n` matching stars, by `fetched_at`.
+    pub max_per_user: Option<usize>,
     pub page: usize,
     pub page_size: usize,
+    /// Return every matching row, ignoring `page` and `page_size`.
+    pub all: bool,
 }
 
 impl Default for StarQuery {
@@ -57,17 +159,36 @@ impl Default for StarQuery {
         Self {
             search: None,
             language: None,
+            topic: None,
             activity: None,
             user: None,
             user_mode: UserFilterMode::All,
             sort: StarSort::Newest,
+            since_sequence: None,
+            pushed_since: None,
+            max_per_user: None,
             page: 1,
             page_size: 25,
+            all: false,
         }
     }
 }
 
 impl StarQuery {
+    pub fn has_filters(&self) -> bool {
+        let present = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|v| !v.trim().is_empty())
+                .unwrap_or(false)
+        };
+        present(&self.search)
+            || present(&self.language)
+            || present(&self.topic)
+            || present(&self.activity)
+            || (present(&self.user) && self.user_mode != UserFilterMode::All)
+    }
+
     pub fn normalized_key(&self) -> String {
         let mut parts = BTreeMap::new();
         if let Some(value) = self
@@ -84,7 +205,15 @@ impl StarQuery {
             .map(|v| v.trim())
             .filter(|v| !v.is_empty())
         {
-            parts.insert("language", value.to_string());
+            parts.insert("language", canonical_language(value));
+        }
+        if let Some(value) = self
+            .topic
+            .as_ref()
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+        {
+            parts.insert("topic", value.to_lowercase());
         }
         if let Some(value) = self
             .activity
@@ -104,8 +233,24 @@ impl StarQuery {
         }
         parts.insert("user_mode", self.user_mode.as_str().to_string());
         parts.insert("sort", self.sort.as_str().to_string());
-        parts.insert("page", self.page().to_string());
-        parts.insert("page_size", self.page_size().to_string());
+        if let StarSort::Random(Some(seed)) = self.sort {
+            parts.insert("seed", seed.to_string());
+        }
+        if let Some(sequence) = self.since_sequence {
+            parts.insert("since_sequence", sequence.to_string());
+        }
+        if let Some(pushed_since) = self.pushed_since {
+            parts.insert("pushed_since", pushed_since.to_rfc3339());
+        }
+        if let Some(cap) = self.max_per_user {
+            parts.insert("max_per_user", cap.to_string());
+        }
+        if self.all {
+            parts.insert("all", "true".to_string());
+        } else {
+            parts.insert("page", self.page().to_string());
+            parts.insert("page_size", self.page_size().to_string());
+        }
         parts
             .into_iter()
             .map(|(k, v)| format!("{k}={v}"))
@@ -113,6 +258,80 @@ impl StarQuery {
             .join("&")
     }
 
+    /// Human-readable summary of the active filters, e.g.
+    /// `language Rust, excluding bot`. `None` when nothing is filtered.
+    pub fn describe_filters(&self) -> Option<String> {
+        let trimmed = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let mut parts = Vec::new();
+        if let Some(search) = trimmed(&self.search) {
+            parts.push(format!("matching \"{search}\""));
+        }
+        if let Some(language) = trimmed(&self.language) {
+            parts.push(format!("language {language}"));
+        }
+        if let Some(topic) = trimmed(&self.topic) {
+            parts.push(format!("topic {}", topic.to_lowercase()));
+        }
+        if let Some(activity) = trimmed(&self.activity) {
+            parts.push(format!("{activity} activity"));
+        }
+        if let Some(user) = trimmed(&self.user) {
+            match self.user_mode {
+                UserFilterMode::Pin => parts.push(format!("starred by {user}")),
+                UserFilterMode::Exclude => parts.push(format!("excluding {user}")),
+                UserFilterMode::All => {}
+            }
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// The `/feed.xml` query string that reproduces this filter, leaving out
+    /// paging and defaults so the feed's self link stays stable.
+    pub fn feed_query_string(&self) -> String {
+        let trimmed = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(search) = trimmed(&self.search) {
+            query.append_pair("q", &search);
+        }
+        if let Some(language) = trimmed(&self.language) {
+            query.append_pair("language", &language);
+        }
+        if let Some(topic) = trimmed(&self.topic) {
+            query.append_pair("topic", &topic);
+        }
+        if let Some(activity) = trimmed(&self.activity) {
+            query.append_pair("activity", &activity);
+        }
+        if let Some(user) = trimmed(&self.user)
+            && self.user_mode != UserFilterMode::All
+        {
+            query.append_pair("user_mode", self.user_mode.as_str());
+            query.append_pair("user", &user);
+        }
+        if self.sort != StarSort::Newest {
+            query.append_pair("sort", self.sort.as_str());
+        }
+        if let StarSort::Random(Some(seed)) = self.sort {
+            query.append_pair("seed", &seed.to_string());
+        }
+        query.finish()
+    }
+
+    /// Whether repeating the query returns the same rows; an unseeded shuffle does not.
+    pub fn is_deterministic(&self) -> bool {
+        !matches!(self.sort, StarSort::Random(None))
+    }
+
     pub fn page(&self) -> usize {
         self.page.max(1)
     }
@@ -129,11 +348,26 @@ pub struct StarQueryResult {
     pub newest_fetched_at: Option<DateTime<Utc>>,
 }
 
+/// One page of [`stars_fetched_since`].
+#[derive(Debug, Clone, Default)]
+pub struct FetchedSincePage {
+    pub items: Vec<StarFeedRow>,
+    pub has_more: bool,
+}
+
+/// Row count and newest `fetched_at` for a query, without loading any rows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StarQueryStats {
+    pub total: usize,
+    pub newest_fetched_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct OptionsSnapshot {
     pub languages: Vec<LanguageStat>,
     pub activity: Vec<ActivityTierStat>,
     pub users: Vec<UserStat>,
+    pub topics: Vec<TopicStat>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
@@ -147,7 +381,13 @@ impl OptionsSnapshot {
             parts.push(format!("activity:{}={}", tier.tier, tier.count));
         }
         for user in &self.users {
-            parts.push(format!("user:{}={}", user.login, user.count));
+            parts.push(format!(
+                "user:{}({})={}",
+                user.login, user.display_name, user.count
+            ));
+        }
+        for topic in &self.topics {
+            parts.push(format!("topic:{}={}", topic.name, topic.count));
         }
         if let Some(updated) = self.updated_at {
             parts.push(format!("updated={}", updated.to_rfc3339()));
@@ -175,19 +415,31 @@ pub struct UserStat {
     pub count: u32,
 }
 
+/// A repository topic, lowercased, with the number of stars carrying it.
+#[derive(Debug, Clone)]
+pub struct TopicStat {
+    pub name: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageTrendPoint {
+    pub date: NaiveDate,
+    pub count: u32,
+}
+
+/// Earliest upcoming check per activity tier; users without a tier are keyed
+/// `unknown`.
 #[derive(Debug, Clone, Default)]
 pub struct NextCheckSummary {
-    pub high: Option<DateTime<Utc>>,
-    pub medium: Option<DateTime<Utc>>,
-    pub low: Option<DateTime<Utc>>,
-    pub unknown: Option<DateTime<Utc>>,
+    pub by_tier: BTreeMap<String, DateTime<Utc>>,
 }
 
 pub async fn query_stars(db_path: &Path, query: &StarQuery) -> Result<StarQueryResult> {
     let path = db_path.to_path_buf();
     let query = query.clone();
     let result = tokio::task::spawn_blocking(move || -> rusqlite::Result<StarQueryResult> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         let builder = QueryBuilder::new(&query);
 
         let total = builder.count(&conn)?;
@@ -204,10 +456,32 @@ pub async fn query_stars(db_path: &Path, query: &StarQuery) -> Result<StarQueryR
     Ok(result)
 }
 
-pub async fn options_snapshot(db_path: &Path) -> Result<OptionsSnapshot> {
+pub async fn query_stats(db_path: &Path, query: &StarQuery) -> Result<StarQueryStats> {
+    let path = db_path.to_path_buf();
+    let query = query.clone();
+    let stats = tokio::task::spawn_blocking(move || -> rusqlite::Result<StarQueryStats> {
+        let conn = open_connection(&path)?;
+        let builder = QueryBuilder::new(&query);
+        Ok(StarQueryStats {
+            total: builder.count(&conn)?,
+            newest_fetched_at: builder.max_fetched(&conn)?,
+        })
+    })
+    .await??;
+    Ok(stats)
+}
+
+pub async fn options_snapshot(
+    db_path: &Path,
+    filter: Option<&StarQuery>,
+) -> Result<OptionsSnapshot> {
     let path = db_path.to_path_buf();
+    let filter = filter.cloned();
     let snapshot = tokio::task::spawn_blocking(move || -> rusqlite::Result<OptionsSnapshot> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
+        if let Some(query) = filter {
+            return faceted_options(&conn, &query);
+        }
 
         let mut languages_stmt = conn.prepare(
             "SELECT repo_language, COUNT(*) as count
@@ -224,6 +498,7 @@ pub async fn options_snapshot(db_path: &Path) -> Result<OptionsSnapshot> {
                 })
             })?
             .collect::<rusqlite::Result<Vec<_>>>()?;
+        let languages = merge_language_stats(languages);
 
         let mut activity_stmt = conn.prepare(
             "SELECT COALESCE(activity_tier, 'unknown') as tier, COUNT(*) as count
@@ -241,21 +516,26 @@ pub async fn options_snapshot(db_path: &Path) -> Result<OptionsSnapshot> {
             .collect::<rusqlite::Result<Vec<_>>>()?;
 
         let mut users_stmt = conn.prepare(
-            "SELECT u.login, COUNT(*) as count
+            "SELECT u.login, u.display_name, COUNT(*) as count
              FROM stars s
              INNER JOIN users u ON u.user_id = s.user_id
              GROUP BY u.user_id, u.login
              ORDER BY count DESC, u.login ASC",
         )?;
         let users = users_stmt
-            .query_map([], |row| {
-                let login: String = row.get(0)?;
-                Ok(UserStat {
-                    display_name: login.clone(),
-                    login,
-                    count: row.get::<_, i64>(1)? as u32,
-                })
-            })?
+            .query_map([], user_stat_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Counted from the table the `topic=` filter matches, so the two agree.
+        let mut topics_stmt = conn.prepare(
+            "SELECT t.topic, COUNT(*) as count
+             FROM stars s
+             JOIN repo_topics t ON t.star_id = s.id
+             GROUP BY t.topic
+             ORDER BY count DESC, t.topic ASC",
+        )?;
+        let topics = topics_stmt
+            .query_map([], topic_stat_from_row)?
             .collect::<rusqlite::Result<Vec<_>>>()?;
 
         let newest_fetched = conn
@@ -271,6 +551,7 @@ pub async fn options_snapshot(db_path: &Path) -> Result<OptionsSnapshot> {
             languages,
             activity,
             users,
+            topics,
             updated_at: newest_fetched,
         })
     })
@@ -278,10 +559,336 @@ pub async fn options_snapshot(db_path: &Path) -> Result<OptionsSnapshot> {
     Ok(snapshot)
 }
 
+/// Computes option counts within the active filters. Each facet ignores its own
+/// filter so the dropdown still lists the alternatives the user could switch to.
+fn faceted_options(conn: &Connection, query: &StarQuery) -> rusqlite::Result<OptionsSnapshot> {
+    let base = StarQuery {
+        since_sequence: None,
+        ..query.clone()
+    };
+
+    let language_builder = QueryBuilder::new(&StarQuery {
+        language: None,
+        ..base.clone()
+    });
+    let sql = format!(
+        "SELECT s.repo_language, COUNT(*) as count
+         FROM stars s
+         INNER JOIN users u ON u.user_id = s.user_id
+         {}
+         GROUP BY s.repo_language
+         ORDER BY count DESC, s.repo_language ASC",
+        language_builder.where_with("s.repo_language IS NOT NULL AND s.repo_language != ''")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let languages = stmt
+        .query_map(params_from_iter(language_builder.bindings.iter()), |row| {
+            Ok(LanguageStat {
+                name: row.get::<_, String>(0)?,
+                count: row.get::<_, i64>(1)? as u32,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let languages = merge_language_stats(languages);
+
+    let activity_builder = QueryBuilder::new(&StarQuery {
+        activity: None,
+        ..base.clone()
+    });
+    let sql = format!(
+        "SELECT COALESCE(u.activity_tier, 'unknown') as tier, COUNT(DISTINCT u.user_id) as count
+         FROM stars s
+         INNER JOIN users u ON u.user_id = s.user_id
+         {}
+         GROUP BY tier
+         ORDER BY count DESC, tier ASC",
+        activity_builder.base_where
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let activity = stmt
+        .query_map(params_from_iter(activity_builder.bindings.iter()), |row| {
+            Ok(ActivityTierStat {
+                tier: row.get::<_, String>(0)?,
+                count: row.get::<_, i64>(1)? as u32,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let user_builder = QueryBuilder::new(&StarQuery {
+        user: None,
+        user_mode: UserFilterMode::All,
+        ..base.clone()
+    });
+    let sql = format!(
+        "SELECT u.login, u.display_name, COUNT(*) as count
+         FROM stars s
+         INNER JOIN users u ON u.user_id = s.user_id
+         {}
+         GROUP BY u.user_id, u.login
+         ORDER BY count DESC, u.login ASC",
+        user_builder.base_where
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let users = stmt
+        .query_map(
+            params_from_iter(user_builder.bindings.iter()),
+            user_stat_from_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let topic_builder = QueryBuilder::new(&StarQuery {
+        topic: None,
+        ..base.clone()
+    });
+    let sql = format!(
+        "SELECT t.topic, COUNT(*) as count
+         FROM stars s
+         INNER JOIN users u ON u.user_id = s.user_id
+         JOIN repo_topics t ON t.star_id = s.id
+         {}
+         GROUP BY t.topic
+         ORDER BY count DESC, t.topic ASC",
+        topic_builder.base_where
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let topics = stmt
+        .query_map(
+            params_from_iter(topic_builder.bindings.iter()),
+            topic_stat_from_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let updated_at = QueryBuilder::new(&base).max_fetched(conn)?;
+
+    Ok(OptionsSnapshot {
+        languages,
+        activity,
+        users,
+        topics,
+        updated_at,
+    })
+}
+
+/// Per-language daily star counts for the `days` UTC days ending at `now`.
+/// Only days with at least one star are returned; aliased languages are merged.
+pub async fn language_trend(
+    db_path: &Path,
+    days: u32,
+    now: DateTime<Utc>,
+) -> Result<BTreeMap<String, Vec<LanguageTrendPoint>>> {
+    let path = db_path.to_path_buf();
+    let first_day = now.date_naive() - Duration::days(i64::from(days.max(1)) - 1);
+    let trend = tokio::task::spawn_blocking(
+        move || -> rusqlite::Result<BTreeMap<String, Vec<LanguageTrendPoint>>> {
+            let conn = open_connection(&path)?;
+            let mut stmt = conn.prepare(
+                "SELECT repo_language, date(starred_at) AS day, COUNT(*) as count
+                 FROM stars
+                 WHERE repo_language IS NOT NULL AND repo_language != ''
+                   AND date(starred_at) >= ?1
+                 GROUP BY repo_language, day
+                 ORDER BY day ASC",
+            )?;
+            let rows = stmt.query_map([first_day.format("%Y-%m-%d").to_string()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? as u32,
+                ))
+            })?;
+
+            let mut trend: BTreeMap<String, Vec<LanguageTrendPoint>> = BTreeMap::new();
+            for row in rows {
+                let (language, day, count) = row?;
+                let date = NaiveDate::parse_from_str(&day, "%Y-%m-%d").map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        1,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?;
+                let points = trend.entry(canonical_language(&language)).or_default();
+                match points.last_mut() {
+                    Some(last) if last.date == date => last.count += count,
+                    _ => points.push(LanguageTrendPoint { date, count }),
+                }
+            }
+            Ok(trend)
+        },
+    )
+    .await??;
+    Ok(trend)
+}
+
+/// Ordering for [`repos_by_followers`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RepoSort {
+    /// Most distinct followers first, newest star breaking ties.
+    #[default]
+    Followers,
+    /// Most recently starred first.
+    Recent,
+}
+
+/// One repository starred by the tracked network.
+#[derive(Debug, Clone)]
+pub struct RepoSummary {
+    pub repo_full_name: String,
+    pub repo_html_url: String,
+    pub repo_language: Option<String>,
+    /// Logins that starred the repository, sorted case-insensitively.
+    pub followers: Vec<String>,
+    pub last_starred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoPage {
+    pub items: Vec<RepoSummary>,
+    /// Distinct repositories across all pages.
+    pub total: usize,
+}
+
+/// Groups stored stars by [`repo_identity`]. Name casing, URL and language
+/// come from the newest star, since they can change between stars of the
+/// same repository.
+pub async fn repos_by_followers(
+    db_path: &Path,
+    sort: RepoSort,
+    page: usize,
+    page_size: usize,
+) -> Result<RepoPage> {
+    let path = db_path.to_path_buf();
+    let page_size = page_size.max(1);
+    let offset = page.saturating_sub(1) * page_size;
+    let order = match sort {
+        RepoSort::Followers => "follower_count DESC, last_starred_at DESC, s.repo_full_name ASC",
+        RepoSort::Recent => "last_starred_at DESC, follower_count DESC, s.repo_full_name ASC",
+    };
+    let result = tokio::task::spawn_blocking(move || -> rusqlite::Result<RepoPage> {
+        let conn = open_connection(&path)?;
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(DISTINCT {REPO_IDENTITY_SQL}) FROM stars s"),
+            [],
+            |row| row.get(0),
+        )?;
+        // SQLite takes bare columns from the row that produced MAX(), so name,
+        // URL and language belong to the newest star.
+        let sql = format!(
+            "SELECT s.repo_full_name, s.repo_html_url, s.repo_language,
+                    MAX(s.starred_at) AS last_starred_at,
+                    COUNT(DISTINCT s.user_id) AS follower_count,
+                    json_group_array(DISTINCT u.login) AS logins
+             FROM stars s
+             INNER JOIN users u ON u.user_id = s.user_id
+             GROUP BY {REPO_IDENTITY_SQL}
+             ORDER BY {order}
+             LIMIT ?1 OFFSET ?2"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(params![page_size as i64, offset as i64])?;
+        let mut items = Vec::new();
+        while let Some(row) = rows.next()? {
+            let language: Option<String> = row.get(2)?;
+            let last_starred: String = row.get(3)?;
+            let logins: String = row.get(5)?;
+            let mut followers: Vec<String> = serde_json::from_str(&logins).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    5,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?;
+            followers.sort_by_key(|login| login.to_lowercase());
+            items.push(RepoSummary {
+                repo_full_name: row.get(0)?,
+                repo_html_url: row.get(1)?,
+                repo_language: language
+                    .filter(|value| !value.is_empty())
+                    .map(|value| canonical_language(&value)),
+                followers,
+                last_starred_at: parse_datetime_sql(&last_starred, 3)?,
+            });
+        }
+        Ok(RepoPage {
+            items,
+            total: total as usize,
+        })
+    })
+    .await??;
+    Ok(result)
+}
+
+pub async fn newest_star_at(db_path: &Path) -> Result<Option<DateTime<Utc>>> {
+    let path = db_path.to_path_buf();
+    let newest = tokio::task::spawn_blocking(move || -> rusqlite::Result<Option<DateTime<Utc>>> {
+        let conn = open_connection(&path)?;
+        conn.query_row("SELECT MAX(starred_at) FROM stars", [], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .optional()?
+        .flatten()
+        .map(|ts| parse_datetime_sql(&ts, 0))
+        .transpose()
+    })
+    .await??;
+    Ok(newest)
+}
+
+/// Id of the most recently stored star; ids only grow, so a change means new stars.
+pub async fn newest_star_id(db_path: &Path) -> Result<Option<i64>> {
+    let path = db_path.to_path_buf();
+    let newest = tokio::task::spawn_blocking(move || -> rusqlite::Result<Option<i64>> {
+        let conn = open_connection(&path)?;
+        conn.query_row("SELECT MAX(id) FROM stars", [], |row| row.get(0))
+    })
+    .await??;
+    Ok(newest)
+}
+
+/// Stars fetched after `since`, oldest fetch first. A page never splits the
+/// rows sharing one `fetched_at`, so it can run past `limit`; otherwise a
+/// client resuming from the last row's timestamp would skip the rest.
+pub async fn stars_fetched_since(
+    db_path: &Path,
+    since: DateTime<Utc>,
+    limit: usize,
+) -> Result<FetchedSincePage> {
+    let path = db_path.to_path_buf();
+    let page = tokio::task::spawn_blocking(move || -> rusqlite::Result<FetchedSincePage> {
+        let conn = open_connection(&path)?;
+        let sql = format!(
+            "SELECT {STAR_COLUMNS}
+             FROM stars s
+             INNER JOIN users u ON u.user_id = s.user_id
+             WHERE s.fetched_at > ?1
+             ORDER BY s.fetched_at ASC, s.id ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(params![since.to_rfc3339()])?;
+        let mut page = FetchedSincePage::default();
+        while let Some(row) = rows.next()? {
+            let star = star_feed_row_from_row(row)?;
+            if page.items.len() >= limit
+                && page
+                    .items
+                    .last()
+                    .is_none_or(|last| last.fetched_at != star.fetched_at)
+            {
+                page.has_more = true;
+                break;
+            }
+            page.items.push(star);
+        }
+        Ok(page)
+    })
+    .await??;
+    Ok(page)
+}
+
 pub async fn next_check_summary(db_path: &Path) -> Result<NextCheckSummary> {
     let path = db_path.to_path_buf();
     let summary = tokio::task::spawn_blocking(move || -> rusqlite::Result<NextCheckSummary> {
-        let conn = Connection::open(path)?;
+        let conn = open_connection(&path)?;
         let mut stmt = conn.prepare(
             "SELECT COALESCE(activity_tier, 'unknown') as tier, MIN(next_check_at)
              FROM users
@@ -296,12 +903,8 @@ pub async fn next_check_summary(db_path: &Path) -> Result<NextCheckSummary> {
         })?;
         for row in rows {
             let (tier, ts) = row?;
-            let parsed = ts.map(|value| parse_datetime_sql(&value, 1)).transpose()?;
-            match tier.as_str() {
-                "high" => next.high = parsed,
-                "medium" => next.medium = parsed,
-                "low" => next.low = parsed,
-                _ => next.unknown = parsed,
+            if let Some(value) = ts {
+                next.by_tier.insert(tier, parse_datetime_sql(&value, 1)?);
             }
         }
         Ok(next)
@@ -310,6 +913,74 @@ pub async fn next_check_summary(db_path: &Path) -> Result<NextCheckSummary> {
     Ok(summary)
 }
 
+/// Visits every stored star in ingest order without buffering the result set,
+/// so exports stay flat in memory regardless of database size.
+pub async fn stream_stars<F>(db_path: &Path, mut visit: F) -> Result<usize>
+where
+    F: FnMut(StarFeedRow) -> Result<()> + Send + 'static,
+{
+    let path = db_path.to_path_buf();
+    let visited = tokio::task::spawn_blocking(move || -> Result<usize> {
+        let conn = open_connection(&path)?;
+        let sql = format!(
+            "SELECT {STAR_COLUMNS}
+             FROM stars s
+             INNER JOIN users u ON u.user_id = s.user_id
+             ORDER BY s.id ASC"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query([])?;
+        let mut visited = 0usize;
+        while let Some(row) = rows.next()? {
+            visit(star_feed_row_from_row(row)?)?;
+            visited += 1;
+        }
+        Ok(visited)
+    })
+    .await??;
+    Ok(visited)
+}
+
+/// Reads `login, display_name, count`; users without a profile name show their login.
+fn user_stat_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<UserStat> {
+    let login: String = row.get(0)?;
+    let display_name: Option<String> = row.get(1)?;
+    Ok(UserStat {
+        display_name: display_name.unwrap_or_else(|| login.clone()),
+        login,
+        count: row.get::<_, i64>(2)? as u32,
+    })
+}
+
+fn topic_stat_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TopicStat> {
+    Ok(TopicStat {
+        name: row.get(0)?,
+        count: row.get::<_, i64>(1)? as u32,
+    })
+}
+
+fn star_feed_row_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<StarFeedRow> {
+    let starred_at_str: String = row.get(6)?;
+    let starred_at = parse_datetime_sql(&starred_at_str, 6)?;
+    let fetched_at_str: String = row.get(7)?;
+    let fetched_at = parse_datetime_sql(&fetched_at_str, 7)?;
+    let topics_json: Option<String> = row.get(4)?;
+    let topics = parse_topics(topics_json)?;
+    Ok(StarFeedRow {
+        login: row.get(0)?,
+        repo_full_name: row.get(1)?,
+        repo_description: row.get(2)?,
+        repo_language: row.get(3)?,
+        repo_topics: topics,
+        repo_html_url: row.get(5)?,
+        repo_owner_avatar_url: row.get(10)?,
+        starred_at,
+        fetched_at,
+        user_activity_tier: row.get(8)?,
+        ingest_sequence: row.get(9)?,
+    })
+}
+
 struct QueryBuilder {
     base_where: String,
     bindings: Vec<Value>,
@@ -341,11 +1012,26 @@ impl QueryBuilder {
         if let Some(language) = sanitized
             .language
             .as_ref()
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+        {
+            let values = language_match_values(language);
+            let placeholders = vec!["?"; values.len()].join(", ");
+            clauses.push(format!(
+                "LOWER(COALESCE(s.repo_language, '')) IN ({placeholders})"
+            ));
+            bindings.extend(values.into_iter().map(Value::from));
+        }
+
+        if let Some(topic) = sanitized
+            .topic
+            .as_ref()
             .map(|v| v.trim().to_lowercase())
             .filter(|v| !v.is_empty())
         {
-            clauses.push("LOWER(COALESCE(s.repo_language, '')) = ?".to_string());
-            bindings.push(Value::from(language));
+            // Served by idx_repo_topics_topic rather than scanning the JSON column.
+            clauses.push("s.id IN (SELECT star_id FROM repo_topics WHERE topic = ?)".to_string());
+            bindings.push(Value::from(topic));
         }
 
         if let Some(activity) = sanitized
@@ -381,6 +1067,37 @@ impl QueryBuilder {
             }
         }
 
+        if let Some(sequence) = sanitized.since_sequence {
+            clauses.push("s.id > ?".to_string());
+            bindings.push(Value::from(sequence));
+        }
+
+        if let Some(pushed_since) = sanitized.pushed_since {
+            clauses.push("(s.repo_pushed_at IS NULL OR s.repo_pushed_at >= ?)".to_string());
+            bindings.push(Value::from(pushed_since.to_rfc3339()));
+        }
+
+        // Ranked within the other filters, so a user's filtered-out stars don't
+        // use up their share.
+        if let Some(cap) = sanitized.max_per_user {
+            let filtered = if clauses.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", clauses.join(" AND "))
+            };
+            clauses.push(format!(
+                "s.id IN (SELECT id FROM (
+                     SELECT s.id, ROW_NUMBER() OVER (
+                         PARTITION BY s.user_id ORDER BY s.fetched_at DESC, s.id DESC
+                     ) AS user_rank
+                     FROM stars s INNER JOIN users u ON u.user_id = s.user_id
+                     {filtered}
+                 ) WHERE user_rank <= ?)"
+            ));
+            bindings.extend(bindings.clone());
+            bindings.push(Value::from(cap as i64));
+        }
+
         let base_where = if clauses.is_empty() {
             String::new()
         } else {
@@ -394,6 +1111,14 @@ impl QueryBuilder {
         }
     }
 
+    fn where_with(&self, extra: &str) -> String {
+        if self.base_where.is_empty() {
+            format!("WHERE {extra}")
+        } else {
+            format!("{} AND {extra}", self.base_where)
+        }
+    }
+
     fn count(&self, conn: &Connection) -> rusqlite::Result<usize> {
         let sql = format!(
             "SELECT COUNT(*) FROM stars s INNER JOIN users u ON u.user_id = s.user_id {}",
@@ -425,13 +1150,33 @@ impl QueryBuilder {
     }
 
     fn fetch_rows(&self, conn: &Connection) -> rusqlite::Result<Vec<StarFeedRow>> {
-        let order_clause = match self.query.sort {
-            StarSort::Newest => "ORDER BY s.fetched_at DESC, s.id DESC",
-            StarSort::Alpha => "ORDER BY LOWER(s.repo_full_name) ASC, s.fetched_at DESC, s.id DESC",
+        // Incremental sync walks forward from the client's cursor, so it ignores
+        // the requested sort and offset pagination.
+        let (order_clause, offset) = if self.query.since_sequence.is_some() {
+            ("ORDER BY s.id ASC".to_string(), 0)
+        } else {
+            let order_clause = match self.query.sort {
+                StarSort::Newest => "ORDER BY s.fetched_at DESC, s.id DESC".to_string(),
+                StarSort::StarredAt => "ORDER BY s.starred_at DESC, s.id DESC".to_string(),
+                StarSort::Alpha => {
+                    "ORDER BY LOWER(s.repo_full_name) ASC, s.fetched_at DESC, LOWER(u.login) ASC, s.id DESC"
+                        .to_string()
+                }
+                // Multiplicative hash of the id XOR the scrambled seed (SQLite has
+                // no XOR operator, hence `(a | b) - (a & b)`). Scrambling spreads
+                // nearby seeds across the high bits that dominate the ordering.
+                StarSort::Random(Some(seed)) => {
+                    let mix = u64::from(seed).wrapping_mul(2654435761) % 4294967296;
+                    format!(
+                        "ORDER BY (((s.id * 2654435761) % 4294967296) | {mix}) - (((s.id * 2654435761) % 4294967296) & {mix}), s.id"
+                    )
+                }
+                StarSort::Random(None) => "ORDER BY RANDOM()".to_string(),
+            };
+            (order_clause, (self.query.page - 1) * self.query.page_size)
         };
-        let offset = (self.query.page - 1) * self.query.page_size;
         let sql = format!(
-            "SELECT u.login, s.repo_full_name, s.repo_description, s.repo_language, s.repo_topics, s.repo_html_url, s.starred_at, s.fetched_at, u.activity_tier, s.id
+            "SELECT {STAR_COLUMNS}
              FROM stars s
              INNER JOIN users u ON u.user_id = s.user_id
              {where_clause}
@@ -441,31 +1186,18 @@ impl QueryBuilder {
             order_clause = order_clause
         );
 
+        // SQLite treats a negative LIMIT as unbounded.
+        let (limit, offset) = if self.query.all {
+            (-1, 0)
+        } else {
+            (self.query.page_size as i64, offset as i64)
+        };
         let mut params = self.bindings.clone();
-        params.push(Value::from(self.query.page_size as i64));
-        params.push(Value::from(offset as i64));
+        params.push(Value::from(limit));
+        params.push(Value::from(offset));
 
         let mut stmt = conn.prepare(&sql)?;
-        let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
-            let starred_at_str: String = row.get(6)?;
-            let starred_at = parse_datetime_sql(&starred_at_str, 6)?;
-            let fetched_at_str: String = row.get(7)?;
-            let fetched_at = parse_datetime_sql(&fetched_at_str, 7)?;
-            let topics_json: Option<String> = row.get(4)?;
-            let topics = parse_topics(topics_json)?;
-            Ok(StarFeedRow {
-                login: row.get(0)?,
-                repo_full_name: row.get(1)?,
-                repo_description: row.get(2)?,
-                repo_language: row.get(3)?,
-                repo_topics: topics,
-                repo_html_url: row.get(5)?,
-                starred_at,
-                fetched_at,
-                user_activity_tier: row.get(8)?,
-                ingest_sequence: row.get(9)?,
-            })
-        })?;
+        let rows = stmt.query_map(params_from_iter(params.iter()), star_feed_row_from_row)?;
 
         let mut events = Vec::new();
         for row in rows {
@@ -485,6 +1217,30 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn feed_query_string_keeps_only_filters() {
+        let query = StarQuery {
+            search: Some(" cli tools ".into()),
+            language: Some("C++".into()),
+            user: Some("bot".into()),
+            user_mode: UserFilterMode::Exclude,
+            sort: StarSort::Random(Some(7)),
+            page: 3,
+            page_size: 50,
+            ..StarQuery::default()
+        };
+        assert_eq!(
+            query.feed_query_string(),
+            "q=cli+tools&language=C%2B%2B&user_mode=exclude&user=bot&sort=random&seed=7"
+        );
+
+        let unpinned = StarQuery {
+            user: Some("bot".into()),
+            ..StarQuery::default()
+        };
+        assert_eq!(unpinned.feed_query_string(), "");
+    }
+
     #[tokio::test]
     async fn query_filters_and_paginates() {
         let temp = NamedTempFile::new().unwrap();
@@ -543,6 +1299,242 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn max_per_user_keeps_each_users_newest_matches() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        for (id, login) in [(1, "busy"), (2, "quiet")] {
+            conn.execute(
+                "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at) VALUES (?1, ?2, 60, ?3)",
+                params![id, login, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+        // busy: five Rust stars then a newer Go star; quiet: one older Rust star.
+        let stars = [
+            (1, "busy/r1", "Rust", 50),
+            (1, "busy/r2", "Rust", 40),
+            (1, "busy/r3", "Rust", 30),
+            (1, "busy/r4", "Rust", 20),
+            (1, "busy/r5", "Rust", 10),
+            (1, "busy/go", "Go", 0),
+            (2, "quiet/r", "Rust", 60),
+        ];
+        for (user_id, repo, language, minutes_ago) in stars {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_language, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, ?3, 'https://example.com', ?4, ?4)",
+                params![user_id, repo, language, (now - Duration::minutes(minutes_ago)).to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let repos = |query: StarQuery| {
+            let path = temp.path().to_path_buf();
+            async move {
+                let result = query_stars(&path, &query).await.unwrap();
+                let repos = result
+                    .items
+                    .into_iter()
+                    .map(|row| row.repo_full_name)
+                    .collect::<Vec<_>>();
+                (result.total, repos)
+            }
+        };
+        let (total, capped) = repos(StarQuery {
+            max_per_user: Some(2),
+            ..StarQuery::default()
+        })
+        .await;
+        assert_eq!(total, 3);
+        assert_eq!(capped, ["busy/go", "busy/r5", "quiet/r"]);
+
+        // The newer Go star is filtered out, so it doesn't take one of busy's slots.
+        let (total, rust) = repos(StarQuery {
+            language: Some("Rust".into()),
+            max_per_user: Some(2),
+            ..StarQuery::default()
+        })
+        .await;
+        assert_eq!(total, 3);
+        assert_eq!(rust, ["busy/r5", "busy/r4", "quiet/r"]);
+    }
+
+    #[tokio::test]
+    async fn starred_sort_orders_by_star_time_not_ingest_time() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, 'high')",
+            params![1, "alice", now.to_rfc3339()],
+        )
+        .unwrap();
+        // A backfill ingests an old star after a recent one.
+        for (repo, starred_at, fetched_at) in [
+            (
+                "new/star",
+                now - Duration::hours(1),
+                now - Duration::minutes(10),
+            ),
+            ("old/star", now - Duration::days(300), now),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, NULL, NULL, 'https://example.com', ?3, ?4)",
+                params![1, repo, starred_at.to_rfc3339(), fetched_at.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let newest = query_stars(temp.path(), &StarQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(newest.items[0].repo_full_name, "old/star");
+
+        let query = StarQuery {
+            sort: StarSort::StarredAt,
+            ..StarQuery::default()
+        };
+        let starred = query_stars(temp.path(), &query).await.unwrap();
+        assert_eq!(starred.items[0].repo_full_name, "new/star");
+        assert!(query.normalized_key().contains("sort=starred"));
+    }
+
+    #[tokio::test]
+    async fn alpha_sort_pages_shared_repos_by_login() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        // Same repo, same fetch: only the login tells the rows apart. Bob's row
+        // is inserted last, so an id-only tiebreak would put him first.
+        for (user_id, login) in [(1, "alice"), (2, "Bob"), (3, "carol")] {
+            conn.execute(
+                "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, 'high')",
+                params![user_id, login, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+        for user_id in [3, 1, 2] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, 'acme/widget', NULL, NULL, NULL, 'https://example.com', ?2, ?2)",
+                params![user_id, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let mut logins = Vec::new();
+        for page in 1..=3 {
+            let query = StarQuery {
+                sort: StarSort::Alpha,
+                page,
+                page_size: 1,
+                ..StarQuery::default()
+            };
+            for _ in 0..2 {
+                let result = query_stars(temp.path(), &query).await.unwrap();
+                assert_eq!(result.items.len(), 1);
+                logins.push(result.items[0].login.clone());
+            }
+        }
+        assert_eq!(logins, ["alice", "alice", "Bob", "Bob", "carol", "carol"]);
+    }
+
+    #[tokio::test]
+    async fn since_sequence_returns_ascending_delta() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, 'high')",
+            params![1, "alice", now.to_rfc3339()],
+        )
+        .unwrap();
+        for (index, repo) in ["a/one", "b/two", "c/three"].iter().enumerate() {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, NULL, NULL, 'https://example.com', ?3, ?3)",
+                params![1, repo, (now - Duration::minutes(index as i64)).to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let query = StarQuery {
+            since_sequence: Some(1),
+            page: 3,
+            ..StarQuery::default()
+        };
+        let result = query_stars(temp.path(), &query).await.unwrap();
+        assert_eq!(result.total, 2);
+        let sequences = result
+            .items
+            .iter()
+            .map(|row| row.ingest_sequence)
+            .collect::<Vec<_>>();
+        assert_eq!(sequences, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn fetched_since_pages_without_splitting_a_fetch() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, 'high')",
+            params![1, "alice", now.to_rfc3339()],
+        )
+        .unwrap();
+        // The last two rows were written by one poll and share a fetch time.
+        for (repo, fetched_minutes_ago) in [("a/old", 30), ("b/mid", 20), ("c/x", 10), ("d/y", 10)]
+        {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, NULL, NULL, 'https://example.com', ?3, ?4)",
+                params![
+                    1,
+                    repo,
+                    now.to_rfc3339(),
+                    (now - Duration::minutes(fetched_minutes_ago)).to_rfc3339()
+                ],
+            )
+            .unwrap();
+        }
+
+        let repos = |page: &FetchedSincePage| {
+            page.items
+                .iter()
+                .map(|row| row.repo_full_name.clone())
+                .collect::<Vec<_>>()
+        };
+        let first = stars_fetched_since(temp.path(), now - Duration::minutes(25), 2)
+            .await
+            .unwrap();
+        assert_eq!(repos(&first), vec!["b/mid", "c/x", "d/y"]);
+        assert!(!first.has_more);
+
+        let first = stars_fetched_since(temp.path(), now - Duration::hours(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(repos(&first), vec!["a/old"]);
+        assert!(first.has_more);
+        let next = first.items.last().unwrap().fetched_at;
+        let rest = stars_fetched_since(temp.path(), next, 10).await.unwrap();
+        assert_eq!(repos(&rest), vec!["b/mid", "c/x", "d/y"]);
+        assert!(!rest.has_more);
+    }
+
     #[tokio::test]
     async fn options_snapshot_counts_entities() {
         let temp = NamedTempFile::new().unwrap();
@@ -561,12 +1553,145 @@ mod tests {
         )
         .unwrap();
 
-        let snapshot = options_snapshot(temp.path()).await.unwrap();
+        let snapshot = options_snapshot(temp.path(), None).await.unwrap();
         assert_eq!(snapshot.languages.len(), 1);
         assert_eq!(snapshot.languages[0].name, "Rust");
         assert_eq!(snapshot.languages[0].count, 1);
         assert_eq!(snapshot.users[0].login, "alice");
+        assert_eq!(snapshot.users[0].display_name, "alice");
         assert!(snapshot.updated_at.is_some());
+
+        crate::db::record_display_name(temp.path(), 1, Some("Alice Smith".into()), now)
+            .await
+            .unwrap();
+        let named = options_snapshot(temp.path(), None).await.unwrap();
+        assert_eq!(named.users[0].display_name, "Alice Smith");
+        assert_ne!(named.fingerprint(), snapshot.fingerprint());
+    }
+
+    #[tokio::test]
+    async fn options_snapshot_counts_topics() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (1, 'alice', ?1, ?1, 30, ?1, 'high')",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for (repo, language, topics) in [
+            (
+                "rust-lang/rust",
+                "Rust",
+                Some(r#"["compiler","Rust","rust"]"#),
+            ),
+            ("tokio-rs/tokio", "Rust", Some(r#"["rust","async"]"#)),
+            ("golang/go", "Go", Some(r#"["compiler"]"#)),
+            ("pallets/flask", "Python", None),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, NULL, ?2, ?3, 'https://example.com', ?4, ?4)",
+                params![repo, language, topics, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+        // Index the JSON written above, as init does for older databases.
+        init(temp.path()).await.unwrap();
+
+        let counts = |snapshot: &OptionsSnapshot| {
+            snapshot
+                .topics
+                .iter()
+                .map(|topic| (topic.name.clone(), topic.count))
+                .collect::<Vec<_>>()
+        };
+        let snapshot = options_snapshot(temp.path(), None).await.unwrap();
+        assert_eq!(
+            counts(&snapshot),
+            [
+                ("compiler".to_string(), 2),
+                ("rust".to_string(), 2),
+                ("async".to_string(), 1)
+            ]
+        );
+
+        let go = StarQuery {
+            language: Some("Go".to_string()),
+            ..StarQuery::default()
+        };
+        let narrowed = options_snapshot(temp.path(), Some(&go)).await.unwrap();
+        assert_eq!(counts(&narrowed), [("compiler".to_string(), 1)]);
+
+        // Facet counts match what the `topic=` filter returns.
+        let rust = StarQuery {
+            topic: Some("rust".to_string()),
+            ..StarQuery::default()
+        };
+        assert_eq!(query_stars(temp.path(), &rust).await.unwrap().total, 2);
+
+        conn.execute(
+            "INSERT INTO repo_topics (star_id, topic)
+             SELECT id, 'go' FROM stars WHERE repo_full_name = 'golang/go'",
+            [],
+        )
+        .unwrap();
+        let retagged = options_snapshot(temp.path(), None).await.unwrap();
+        assert_ne!(retagged.fingerprint(), snapshot.fingerprint());
+    }
+
+    #[tokio::test]
+    async fn options_snapshot_narrows_other_facets_to_filter() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        for (user_id, login, tier) in [(1, "alice", "high"), (2, "bob", "low")] {
+            conn.execute(
+                "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, ?4)",
+                params![user_id, login, now.to_rfc3339(), tier],
+            )
+            .unwrap();
+        }
+        for (user_id, repo, language) in [
+            (1, "rust-lang/rust", "Rust"),
+            (1, "golang/go", "Go"),
+            (2, "tokio-rs/tokio", "Rust"),
+            (2, "python/cpython", "Python"),
+            (2, "pallets/flask", "Python"),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, ?3, NULL, 'https://example.com', ?4, ?4)",
+                params![user_id, repo, language, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let query = StarQuery {
+            language: Some("Rust".to_string()),
+            ..StarQuery::default()
+        };
+        let snapshot = options_snapshot(temp.path(), Some(&query)).await.unwrap();
+        let user_counts = snapshot
+            .users
+            .iter()
+            .map(|u| (u.login.as_str(), u.count))
+            .collect::<Vec<_>>();
+        assert_eq!(user_counts, vec![("alice", 1), ("bob", 1)]);
+        assert_eq!(snapshot.languages.len(), 3);
+        assert_eq!(snapshot.activity.len(), 2);
+
+        let pinned = StarQuery {
+            user: Some("bob".to_string()),
+            user_mode: UserFilterMode::Pin,
+            ..StarQuery::default()
+        };
+        let snapshot = options_snapshot(temp.path(), Some(&pinned)).await.unwrap();
+        assert_eq!(snapshot.languages[0].name, "Python");
+        assert_eq!(snapshot.languages[0].count, 2);
+        assert_eq!(snapshot.languages.len(), 2);
     }
 
     #[tokio::test]
@@ -587,7 +1712,296 @@ mod tests {
         .unwrap();
 
         let summary = next_check_summary(temp.path()).await.unwrap();
-        assert!(summary.high.is_some());
-        assert!(summary.unknown.is_some());
+        assert!(summary.by_tier.contains_key("high"));
+        assert!(summary.by_tier.contains_key("unknown"));
+    }
+
+    #[tokio::test]
+    async fn language_aliases_resolve_to_canonical_names() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (1, 'alice', ?1, ?1, 30, ?1, 'high')",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for (repo, language) in [
+            ("a/cpp", "C++"),
+            ("a/vim-old", "Vim script"),
+            ("a/vim-new", "Vim Script"),
+            ("a/zig", "Zig"),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, NULL, ?2, NULL, 'https://example.com', ?3, ?3)",
+                params![repo, language, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        for alias in ["c++", "cpp", "CPlusPlus"] {
+            let query = StarQuery {
+                language: Some(alias.to_string()),
+                ..StarQuery::default()
+            };
+            let result = query_stars(temp.path(), &query).await.unwrap();
+            assert_eq!(result.total, 1, "alias {alias}");
+            assert_eq!(result.items[0].repo_full_name, "a/cpp");
+        }
+
+        let query = StarQuery {
+            language: Some("viml".to_string()),
+            ..StarQuery::default()
+        };
+        assert_eq!(query_stars(temp.path(), &query).await.unwrap().total, 2);
+        assert!(query.normalized_key().starts_with("language=Vim Script&"));
+
+        let snapshot = options_snapshot(temp.path(), None).await.unwrap();
+        let names: Vec<_> = snapshot
+            .languages
+            .iter()
+            .map(|lang| (lang.name.as_str(), lang.count))
+            .collect();
+        assert_eq!(names, vec![("Vim Script", 2), ("C++", 1), ("Zig", 1)]);
+        assert_eq!(canonical_language("Zig"), "Zig");
+    }
+
+    #[tokio::test]
+    async fn language_trend_buckets_by_day_within_window() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (1, 'alice', ?1, ?1, 30, ?1, 'high')",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for (repo, language, age_days) in [
+            ("a/one", "Rust", 0),
+            ("a/two", "rs", 0),
+            ("a/three", "Rust", 2),
+            ("a/four", "Go", 1),
+            ("a/old", "Rust", 40),
+        ] {
+            let starred = (now - Duration::days(age_days)).to_rfc3339();
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, NULL, ?2, NULL, 'https://example.com', ?3, ?3)",
+                params![repo, language, starred],
+            )
+            .unwrap();
+        }
+
+        let trend = language_trend(temp.path(), 30, now).await.unwrap();
+        assert_eq!(trend.len(), 2);
+        let rust = &trend["Rust"];
+        assert_eq!(
+            rust,
+            &vec![
+                LanguageTrendPoint {
+                    date: (now - Duration::days(2)).date_naive(),
+                    count: 1,
+                },
+                LanguageTrendPoint {
+                    date: now.date_naive(),
+                    count: 2,
+                },
+            ]
+        );
+        assert_eq!(trend["Go"][0].count, 1);
+    }
+
+    #[tokio::test]
+    async fn pushed_since_keeps_active_and_unknown_repos() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at) VALUES (1, 'alice', 60, ?1)",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for (repo, pushed_at) in [
+            ("a/active", Some(now - Duration::days(3))),
+            ("a/dead", Some(now - Duration::days(900))),
+            ("a/unknown", None),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_html_url, repo_pushed_at, starred_at, fetched_at)
+                 VALUES (1, ?1, 'https://example.com', ?2, ?3, ?3)",
+                params![repo, pushed_at.map(|ts| ts.to_rfc3339()), now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let query = StarQuery {
+            pushed_since: Some(now - Duration::days(365)),
+            ..StarQuery::default()
+        };
+        let result = query_stars(temp.path(), &query).await.unwrap();
+        let mut repos: Vec<_> = result
+            .items
+            .iter()
+            .map(|row| row.repo_full_name.as_str())
+            .collect();
+        repos.sort();
+        assert_eq!(repos, vec!["a/active", "a/unknown"]);
+        assert_eq!(result.total, 2);
+    }
+
+    #[tokio::test]
+    async fn topic_filter_uses_migrated_join_table() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at) VALUES (1, 'alice', 60, ?1)",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for (repo, topics) in [
+            ("a/tool", Some(r#"["cli","rust"]"#)),
+            ("a/site", Some(r#"["web"]"#)),
+            ("a/bare", None),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, ?2, 'https://example.com', ?3, ?3)",
+                params![repo, topics, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        // Rows written straight to `stars` have no join entries until init backfills them.
+        init(temp.path()).await.unwrap();
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM repo_topics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 3);
+
+        let query = StarQuery {
+            topic: Some(" CLI ".to_string()),
+            ..StarQuery::default()
+        };
+        let result = query_stars(temp.path(), &query).await.unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.items[0].repo_full_name, "a/tool");
+        assert_eq!(result.items[0].repo_topics, vec!["cli", "rust"]);
+    }
+
+    #[tokio::test]
+    async fn repos_group_stars_by_distinct_follower() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        for (user_id, login) in [(1, "carol"), (2, "alice"), (3, "Bob")] {
+            conn.execute(
+                "INSERT INTO users (user_id, login, last_starred_at, last_fetched_at, fetch_interval_minutes, next_check_at, activity_tier) VALUES (?1, ?2, ?3, ?3, 30, ?3, 'high')",
+                params![user_id, login, now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+        // acme/widget: three followers, alice twice, carol under an older
+        // casing; tools/old: one newer star.
+        for (user_id, repo, language, hours_ago) in [
+            (1, "Acme/Widget", "rust", 5),
+            (2, "acme/widget", "Rust", 4),
+            (2, "acme/widget", "Rust", 3),
+            (3, "acme/widget", "Rust", 2),
+            (1, "tools/old", "Go", 1),
+        ] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_description, repo_language, repo_topics, repo_html_url, starred_at, fetched_at)
+                 VALUES (?1, ?2, NULL, ?3, NULL, 'https://example.com/' || ?2, ?4, ?4)",
+                params![
+                    user_id,
+                    repo,
+                    language,
+                    (now - Duration::hours(hours_ago)).to_rfc3339()
+                ],
+            )
+            .unwrap();
+        }
+
+        let page = repos_by_followers(temp.path(), RepoSort::Followers, 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        let widget = &page.items[0];
+        assert_eq!(widget.repo_full_name, "acme/widget");
+        assert_eq!(repo_identity(" Acme/Widget "), repo_identity("acme/widget"));
+        assert_eq!(widget.followers, ["alice", "Bob", "carol"]);
+        assert_eq!(widget.repo_language.as_deref(), Some("Rust"));
+        assert_eq!(
+            widget.last_starred_at.timestamp(),
+            (now - Duration::hours(2)).timestamp()
+        );
+
+        let recent = repos_by_followers(temp.path(), RepoSort::Recent, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(recent.total, 2);
+        assert_eq!(recent.items.len(), 1);
+        assert_eq!(recent.items[0].repo_full_name, "tools/old");
+        let second = repos_by_followers(temp.path(), RepoSort::Recent, 2, 1)
+            .await
+            .unwrap();
+        assert_eq!(second.items[0].repo_full_name, "acme/widget");
+    }
+
+    #[tokio::test]
+    async fn seeded_random_sort_is_a_stable_shuffle() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let now = Utc::now();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at) VALUES (1, 'alice', 60, ?1)",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        for idx in 0..8 {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, 'https://example.com', ?2, ?2)",
+                params![format!("a/repo{idx}"), now.to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let order = |seed: u32| {
+            let query = StarQuery {
+                sort: StarSort::Random(Some(seed)),
+                all: true,
+                ..StarQuery::default()
+            };
+            let path = temp.path().to_path_buf();
+            async move {
+                query_stars(&path, &query)
+                    .await
+                    .unwrap()
+                    .items
+                    .into_iter()
+                    .map(|row| row.ingest_sequence)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let first = order(7).await;
+        assert_eq!(first, order(7).await);
+        assert_ne!(first, order(8).await);
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=8).collect::<Vec<i64>>());
+        assert_ne!(first, sorted);
     }
 }
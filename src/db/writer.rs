@@ -0,0 +1,210 @@
+//! A single writer that funnels every user's star inserts through one
+//! connection, folding whatever is queued into one transaction per flush.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use rusqlite::Connection;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use super::{StarBatch, WriteSettings, open_connection, write_star_batch};
+use crate::config::Config;
+
+/// Most batches committed together; bounds how long one transaction holds the WAL lock.
+const MAX_BATCHES_PER_FLUSH: usize = 64;
+/// Queued batches before `submit` waits for the writer to catch up.
+const QUEUE_DEPTH: usize = 256;
+
+struct WriteJob {
+    batch: StarBatch,
+    reply: oneshot::Sender<Result<i64>>,
+}
+
+/// Handle to the writer task. Clones share the same queue.
+#[derive(Clone)]
+pub struct StarWriter {
+    jobs: mpsc::Sender<WriteJob>,
+}
+
+/// Owns the writer task; dropping every [`StarWriter`] lets it drain and exit.
+pub struct StarWriterTask {
+    handle: JoinHandle<Result<()>>,
+}
+
+impl StarWriter {
+    /// Starts the writer on a blocking thread with its own connection.
+    pub fn spawn(db_path: &Path, config: &Config) -> (Self, StarWriterTask) {
+        Self::spawn_with(db_path, WriteSettings::from(config))
+    }
+
+    fn spawn_with(db_path: &Path, settings: WriteSettings) -> (Self, StarWriterTask) {
+        let (jobs, queue) = mpsc::channel(QUEUE_DEPTH);
+        let path = db_path.to_path_buf();
+        let handle = tokio::task::spawn_blocking(move || run_writer(path, settings, queue));
+        (Self { jobs }, StarWriterTask { handle })
+    }
+
+    /// Queues one user's results and waits for the flush that commits them,
+    /// returning the user's new polling interval.
+    pub async fn submit(&self, batch: StarBatch) -> Result<i64> {
+        let (reply, response) = oneshot::channel();
+        self.jobs
+            .send(WriteJob { batch, reply })
+            .await
+            .map_err(|_| anyhow!("star writer has stopped"))?;
+        response
+            .await
+            .map_err(|_| anyhow!("star writer dropped the write"))?
+    }
+}
+
+impl StarWriterTask {
+    /// Waits for queued writes to finish once every handle has been dropped.
+    pub async fn finish(self) -> Result<()> {
+        self.handle.await?
+    }
+}
+
+fn run_writer(
+    path: PathBuf,
+    settings: WriteSettings,
+    mut queue: mpsc::Receiver<WriteJob>,
+) -> Result<()> {
+    let mut conn = open_connection(&path)?;
+    while let Some(first) = queue.blocking_recv() {
+        let mut jobs = vec![first];
+        while jobs.len() < MAX_BATCHES_PER_FLUSH {
+            match queue.try_recv() {
+                Ok(job) => jobs.push(job),
+                Err(_) => break,
+            }
+        }
+        flush(&mut conn, &settings, jobs);
+    }
+    Ok(())
+}
+
+/// Writes every job in one transaction. Each job runs in its own savepoint, so
+/// a failing user is rolled back without discarding the others.
+fn flush(conn: &mut Connection, settings: &WriteSettings, jobs: Vec<WriteJob>) {
+    let mut results = Vec::with_capacity(jobs.len());
+    let committed = (|| -> rusqlite::Result<()> {
+        let mut tx = conn.transaction()?;
+        for job in &jobs {
+            let savepoint = tx.savepoint()?;
+            match write_star_batch(&savepoint, &job.batch, settings) {
+                Ok(interval) => {
+                    savepoint.commit()?;
+                    results.push(Ok(interval));
+                }
+                // Dropping the savepoint rolls back this user's writes.
+                Err(err) => results.push(Err(anyhow!(err))),
+            }
+        }
+        tx.commit()
+    })();
+
+    match committed {
+        Ok(()) => {
+            for (job, result) in jobs.into_iter().zip(results) {
+                let _ = job.reply.send(result);
+            }
+        }
+        Err(err) => {
+            let message = err.to_string();
+            for job in jobs {
+                let _ = job
+                    .reply
+                    .send(Err(anyhow!("star write transaction failed: {message}")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use rusqlite::params;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::db::{all_users, init, upsert_followings};
+    use crate::github::{FollowingUser, StarEvent};
+
+    fn event(repo: &str) -> StarEvent {
+        StarEvent {
+            repo_full_name: repo.to_string(),
+            repo_description: None,
+            repo_html_url: format!("https://github.com/{repo}"),
+            starred_at: Utc::now(),
+            repo_language: None,
+            repo_topics: Vec::new(),
+            repo_owner_avatar_url: None,
+            repo_pushed_at: None,
+            starred_at_estimated: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_submissions_are_all_committed() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let followings: Vec<_> = (1..=20)
+            .map(|id| FollowingUser {
+                id,
+                login: format!("user{id}"),
+            })
+            .collect();
+        upsert_followings(temp.path(), &followings, 60, "low")
+            .await
+            .unwrap();
+        let users = all_users(temp.path()).await.unwrap();
+
+        let settings = WriteSettings {
+            min_interval: 10,
+            max_interval: 7 * 24 * 60,
+            default_interval: 60,
+            ema_alpha: 0.3,
+            activity_tiers: Default::default(),
+            repo_filter: Default::default(),
+        };
+        let (writer, task) = StarWriter::spawn_with(temp.path(), settings);
+        let mut pending = Vec::new();
+        for user in users {
+            let writer = writer.clone();
+            pending.push(tokio::spawn(async move {
+                let repo = format!("acme/{}", user.login);
+                writer
+                    .submit(StarBatch {
+                        user,
+                        events: vec![event(&repo)],
+                        fetched_at: Utc::now(),
+                        etag: None,
+                        last_modified: None,
+                        poll_interval: None,
+                    })
+                    .await
+            }));
+        }
+        for handle in pending {
+            handle.await.unwrap().unwrap();
+        }
+        drop(writer);
+        task.finish().await.unwrap();
+
+        let conn = Connection::open(temp.path()).unwrap();
+        let stars: i64 = conn
+            .query_row("SELECT COUNT(*) FROM stars", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stars, 20);
+        let counted: i64 = conn
+            .query_row(
+                "SELECT star_count FROM users WHERE user_id = ?1",
+                params![7],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(counted, 1);
+    }
+}
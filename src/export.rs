@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+
+use crate::config::ExportFormat;
+use crate::db::{StarFeedRow, star_query};
+use crate::server::StarEventResponse;
+
+/// Streams every stored star to `output` (stdout when `None`) and returns the row count.
+pub async fn export_stars(
+    db_path: &Path,
+    format: ExportFormat,
+    output: Option<&Path>,
+) -> Result<usize> {
+    let writer: Box<dyn Write + Send> = match output {
+        Some(path) => Box::new(
+            File::create(path)
+                .with_context(|| format!("failed to create export file {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    match format {
+        ExportFormat::Ndjson => export_ndjson(db_path, writer).await,
+    }
+}
+
+pub async fn export_ndjson<W>(db_path: &Path, writer: W) -> Result<usize>
+where
+    W: Write + Send + 'static,
+{
+    let writer = Arc::new(Mutex::new(BufWriter::new(writer)));
+    let row_writer = Arc::clone(&writer);
+    let count = star_query::stream_stars(db_path, move |row| {
+        let mut out = row_writer
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner());
+        serde_json::to_writer(&mut *out, &StarEventResponse::from(row))?;
+        out.write_all(b"\n")?;
+        Ok(())
+    })
+    .await?;
+    writer
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner())
+        .flush()
+        .context("failed to flush export output")?;
+    Ok(count)
+}
+
+const CSV_HEADER: &str = "ingest_sequence,login,repo_full_name,repo_html_url,repo_description,repo_language,repo_topics,starred_at,fetched_at,user_activity_tier";
+
+/// Renders star rows as CSV with a header row. Topics are joined with `;`.
+pub fn stars_to_csv(rows: &[StarFeedRow]) -> String {
+    let mut out = String::with_capacity(CSV_HEADER.len() + rows.len() * 160);
+    out.push_str(CSV_HEADER);
+    out.push_str("\r\n");
+    for row in rows {
+        let fields = [
+            row.ingest_sequence.to_string(),
+            row.login.clone(),
+            row.repo_full_name.clone(),
+            row.repo_html_url.clone(),
+            row.repo_description.clone().unwrap_or_default(),
+            row.repo_language.clone().unwrap_or_default(),
+            row.repo_topics.join(";"),
+            row.starred_at.to_rfc3339(),
+            row.fetched_at.to_rfc3339(),
+            row.user_activity_tier.clone().unwrap_or_default(),
+        ];
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            push_csv_field(&mut out, field);
+        }
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quotes a field per RFC 4180 when it contains a delimiter, quote, or newline.
+fn push_csv_field(out: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+    use serde_json::Value;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::db::init;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_delimiters() {
+        let row = StarFeedRow {
+            login: "alice".into(),
+            repo_full_name: "acme/widget".into(),
+            repo_description: Some("Fast, \"safe\" widgets\nfor all".into()),
+            repo_language: Some("Rust".into()),
+            repo_topics: vec!["cli".into(), "tools".into()],
+            repo_html_url: "https://github.com/acme/widget".into(),
+            repo_owner_avatar_url: None,
+            starred_at: "2025-10-18T04:15:00Z".parse().unwrap(),
+            fetched_at: "2025-10-18T04:16:00Z".parse().unwrap(),
+            user_activity_tier: None,
+            ingest_sequence: 7,
+        };
+        let csv = stars_to_csv(&[row]);
+        let mut lines = csv.split("\r\n");
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "7,alice,acme/widget,https://github.com/acme/widget,\"Fast, \"\"safe\"\" widgets\nfor all\",Rust,cli;tools,2025-10-18T04:15:00+00:00,2025-10-18T04:16:00+00:00,"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn ndjson_writes_one_object_per_line() {
+        let temp = NamedTempFile::new().unwrap();
+        init(temp.path()).await.unwrap();
+        let conn = Connection::open(temp.path()).unwrap();
+        conn.execute(
+            "INSERT INTO users (user_id, login, fetch_interval_minutes, next_check_at, activity_tier)
+             VALUES (1, 'alice', 60, '2025-10-18T05:16:00Z', 'high')",
+            [],
+        )
+        .unwrap();
+        for repo in ["rust-lang/rust", "tokio-rs/tokio"] {
+            conn.execute(
+                "INSERT INTO stars (user_id, repo_full_name, repo_html_url, starred_at, fetched_at)
+                 VALUES (1, ?1, 'https://example.com', '2025-10-18T04:15:00Z', '2025-10-18T04:16:00Z')",
+                [repo],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        let buffer = SharedBuffer::default();
+        let count = export_ndjson(temp.path(), buffer.clone()).await.unwrap();
+        assert_eq!(count, 2);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["repo_full_name"], "rust-lang/rust");
+        assert_eq!(first["ingest_sequence"], 1);
+    }
+}
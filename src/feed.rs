@@ -1,41 +1,99 @@
 use std::cmp::Reverse;
-use std::fs;
-use std::path::PathBuf;
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use html_escape::encode_text;
-use rss::{ChannelBuilder, GuidBuilder, ItemBuilder};
+use html_escape::{encode_double_quoted_attribute, encode_text};
+use rss::extension::atom::{AtomExtensionBuilder, Link};
+use rss::extension::dublincore::DublinCoreExtensionBuilder;
+use rss::{ChannelBuilder, EnclosureBuilder, GuidBuilder, ItemBuilder};
 
+use crate::config::FeedOrder;
 use crate::db::StarFeedRow;
 
+#[cfg(feature = "frontend")]
+mod html;
+
+#[cfg(feature = "frontend")]
+pub use html::{build_html, build_html_from_dir};
+
 const CHANNEL_TITLE: &str = "GitHub Followings Stars";
 const CHANNEL_LINK: &str = "https://github.com";
 const CHANNEL_DESCRIPTION: &str =
     "Aggregated feed of repositories starred by the accounts you follow on GitHub.";
+const OPENGRAPH_BASE: &str = "https://opengraph.githubassets.com/1";
+
+#[derive(Debug, Clone, Default)]
+pub struct FeedOptions {
+    /// Attach the repository's OpenGraph social preview as an `<enclosure>`.
+    pub enclosures: bool,
+    /// Appended to the channel title, e.g. to label a per-tier feed.
+    pub title_suffix: Option<String>,
+    /// With [`FeedOrder::Fetched`], items are ordered and dated by `fetched_at`.
+    pub order: FeedOrder,
+    /// Channel `<link>`; defaults to github.com.
+    pub link: Option<String>,
+    /// Public URL of this feed, published as `<atom:link rel="self">`.
+    pub self_url: Option<String>,
+    /// WebSub hub, published as `<atom:link rel="hub">`.
+    pub hub_url: Option<String>,
+}
 
-pub fn build_feed(events: &[StarFeedRow], generated_at: DateTime<Utc>) -> Result<String> {
+pub fn build_feed(
+    events: &[StarFeedRow],
+    generated_at: DateTime<Utc>,
+    options: &FeedOptions,
+) -> Result<String> {
     let mut sorted = events.to_owned();
-    sorted.sort_by_key(|event| Reverse(event.starred_at));
-    let items = sorted.iter().map(build_item).collect::<Vec<_>>();
+    match options.order {
+        FeedOrder::Starred => sorted.sort_by_key(|event| Reverse(event.starred_at)),
+        FeedOrder::Fetched => {
+            sorted.sort_by_key(|event| Reverse((event.fetched_at, event.ingest_sequence)))
+        }
+    }
+    let items = sorted
+        .iter()
+        .map(|event| build_item(event, options))
+        .collect::<Vec<_>>();
+    let title = match &options.title_suffix {
+        Some(suffix) => format!("{CHANNEL_TITLE} ({suffix})"),
+        None => CHANNEL_TITLE.to_string(),
+    };
+    let mut links = Vec::new();
+    if let Some(href) = &options.self_url {
+        let mut link = Link::default();
+        link.set_href(href.as_str());
+        link.set_rel("self");
+        link.set_mime_type(Some("application/rss+xml".to_string()));
+        links.push(link);
+    }
+    if let Some(href) = &options.hub_url {
+        let mut link = Link::default();
+        link.set_href(href.as_str());
+        link.set_rel("hub");
+        links.push(link);
+    }
+    let atom_ext =
+        (!links.is_empty()).then(|| AtomExtensionBuilder::default().links(links).build());
     let channel = ChannelBuilder::default()
-        .title(CHANNEL_TITLE)
-        .link(CHANNEL_LINK)
+        .title(title)
+        .link(options.link.as_deref().unwrap_or(CHANNEL_LINK))
         .description(CHANNEL_DESCRIPTION)
         .last_build_date(generated_at.to_rfc2822())
+        .atom_ext(atom_ext)
         .items(items)
         .build();
     Ok(channel.to_string())
 }
 
-fn build_item(event: &StarFeedRow) -> rss::Item {
+fn build_item(event: &StarFeedRow, options: &FeedOptions) -> rss::Item {
+    // Readers sort by pubDate, so a late discovery needs its fetch time there to surface.
+    let pub_date = match options.order {
+        FeedOrder::Starred => event.starred_at,
+        FeedOrder::Fetched => event.fetched_at,
+    };
     let title = format!("{} starred {}", event.login, event.repo_full_name);
-    let guid_value = format!(
-        "github-star://{}/{}/{}",
-        event.login,
-        event.repo_full_name,
-        event.starred_at.to_rfc3339()
-    );
+    // No timestamp: a re-star or drift in `starred_at` must not show up as a new item.
+    let guid_value = format!("github-star://{}/{}", event.login, event.repo_full_name);
     let guid = GuidBuilder::default()
         .value(guid_value)
         .permalink(false)
@@ -45,83 +103,155 @@ fn build_item(event: &StarFeedRow) -> rss::Item {
         .as_ref()
         .map(|desc| format!("{}\nStarred by https://github.com/{}", desc, event.login))
         .unwrap_or_else(|| format!("Starred by https://github.com/{}", event.login));
+    // `<dc:creator>` rather than `<author>`, which RSS reserves for an email address;
+    // readers that group by author then collapse items per following.
+    let creator = DublinCoreExtensionBuilder::default()
+        .creators(vec![format!("https://github.com/{}", event.login)])
+        .build();
+    let content = event
+        .repo_owner_avatar_url
+        .as_ref()
+        .map(|avatar| build_item_content(event, avatar));
+    let enclosure = options.enclosures.then(|| {
+        EnclosureBuilder::default()
+            .url(format!("{OPENGRAPH_BASE}/{}", event.repo_full_name))
+            .length("0")
+            .mime_type("image/png")
+            .build()
+    });
     ItemBuilder::default()
         .title(title)
         .link(event.repo_html_url.clone())
         .description(description)
+        .content(content)
+        .enclosure(enclosure)
+        .dublin_core_ext(creator)
         .guid(guid)
-        .pub_date(event.starred_at.to_rfc2822())
+        .pub_date(pub_date.to_rfc2822())
         .build()
 }
 
-pub fn build_html(_events: &[StarFeedRow], generated_at: DateTime<Utc>, base_path: &str) -> String {
-    let generated_at_str = generated_at.to_rfc3339();
-    let last_updated = encode_text(&generated_at_str);
-    let prefix = encode_text(base_path);
-    if cfg!(debug_assertions)
-        && let Some(html) = try_build_html_from_disk(&last_updated, &prefix)
-    {
-        return html;
+fn build_item_content(event: &StarFeedRow, avatar_url: &str) -> String {
+    let owner = event
+        .repo_full_name
+        .split('/')
+        .next()
+        .unwrap_or(&event.repo_full_name);
+    let mut html = format!(
+        "<p><img src=\"{}\" alt=\"{}\" width=\"64\" height=\"64\"></p>",
+        encode_double_quoted_attribute(avatar_url),
+        encode_double_quoted_attribute(owner)
+    );
+    if let Some(desc) = &event.repo_description {
+        html.push_str(&format!("<p>{}</p>", encode_text(desc)));
     }
-    build_html_from_embedded(&last_updated, &prefix)
+    html.push_str(&format!(
+        "<p>Starred by <a href=\"https://github.com/{login}\">{login}</a></p>",
+        login = encode_text(&event.login)
+    ));
+    html
 }
 
-fn build_html_from_embedded(last_updated: &str, base_path: &str) -> String {
-    static EMBEDDED_TEMPLATE: &str = include_str!(concat!(env!("OUT_DIR"), "/frontend_index.html"));
-    EMBEDDED_TEMPLATE
-        .replace("__LAST_UPDATED__", last_updated)
-        .replace("__BASE_PATH__", base_path)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn try_build_html_from_disk(last_updated: &str, base_path: &str) -> Option<String> {
-    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let template_path = manifest_dir.join("frontend/index.html");
-    let styles_path = manifest_dir.join("frontend/styles.css");
-    let script_path = manifest_dir.join("frontend/app.js");
-
-    let template = match fs::read_to_string(&template_path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            eprintln!(
-                "hoshiyomi: falling back to embedded frontend (failed to read {}): {}",
-                template_path.display(),
-                err
-            );
-            return None;
+    fn row(repo: &str, starred_day: u32, fetched_day: u32, sequence: i64) -> StarFeedRow {
+        use chrono::TimeZone;
+        StarFeedRow {
+            login: "alice".into(),
+            repo_full_name: repo.into(),
+            repo_description: None,
+            repo_language: None,
+            repo_topics: Vec::new(),
+            repo_html_url: format!("https://github.com/{repo}"),
+            repo_owner_avatar_url: None,
+            starred_at: Utc.with_ymd_and_hms(2025, 1, starred_day, 0, 0, 0).unwrap(),
+            fetched_at: Utc.with_ymd_and_hms(2025, 1, fetched_day, 0, 0, 0).unwrap(),
+            user_activity_tier: None,
+            ingest_sequence: sequence,
         }
-    };
+    }
 
-    let styles = match fs::read_to_string(&styles_path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            eprintln!(
-                "hoshiyomi: falling back to embedded frontend (failed to read {}): {}",
-                styles_path.display(),
-                err
-            );
-            return None;
-        }
-    };
+    #[test]
+    fn guid_is_stable_when_starred_at_drifts() {
+        let first = row("acme/widget", 9, 10, 1);
+        let mut drifted = first.clone();
+        drifted.starred_at += chrono::Duration::microseconds(250);
+        let guids = [first, drifted].map(|event| {
+            let xml = build_feed(&[event], Utc::now(), &FeedOptions::default()).unwrap();
+            let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+            channel.items()[0].guid().unwrap().value().to_string()
+        });
+        assert_eq!(guids[0], "github-star://alice/acme/widget");
+        assert_eq!(guids[0], guids[1]);
+    }
 
-    let script = match fs::read_to_string(&script_path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            eprintln!(
-                "hoshiyomi: falling back to embedded frontend (failed to read {}): {}",
-                script_path.display(),
-                err
-            );
-            return None;
-        }
-    };
+    #[test]
+    fn items_credit_the_starring_user_as_dc_creator() {
+        let xml = build_feed(
+            &[row("acme/widget", 9, 10, 1)],
+            Utc::now(),
+            &FeedOptions::default(),
+        )
+        .unwrap();
+        assert!(xml.contains("xmlns:dc=\"http://purl.org/dc/elements/1.1/\""));
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+        let creators = channel.items()[0].dublin_core_ext().unwrap().creators();
+        assert_eq!(creators, ["https://github.com/alice"]);
+    }
 
-    let bundled = template
-        .replace("{{STYLE}}", styles.trim())
-        .replace("{{SCRIPT}}", script.trim());
+    #[test]
+    fn fetched_order_puts_late_discoveries_first() {
+        // An old star found by a backfill on the 20th, after a recent star fetched on the 10th.
+        let events = [row("acme/recent", 9, 10, 1), row("acme/old", 1, 20, 2)];
+        let titles = |order| {
+            let options = FeedOptions {
+                order,
+                ..FeedOptions::default()
+            };
+            let xml = build_feed(&events, Utc::now(), &options).unwrap();
+            let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+            channel
+                .items()
+                .iter()
+                .map(|item| {
+                    (
+                        item.title().unwrap().to_string(),
+                        item.pub_date().unwrap().to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
 
-    Some(
-        bundled
-            .replace("__LAST_UPDATED__", last_updated)
-            .replace("__BASE_PATH__", base_path),
-    )
+        let starred = titles(FeedOrder::Starred);
+        assert_eq!(starred[0].0, "alice starred acme/recent");
+        let fetched = titles(FeedOrder::Fetched);
+        assert_eq!(fetched[0].0, "alice starred acme/old");
+        assert_eq!(fetched[0].1, "Mon, 20 Jan 2025 00:00:00 +0000");
+    }
+
+    #[test]
+    fn public_url_sets_channel_self_and_hub_links() {
+        let options = FeedOptions {
+            link: Some("https://example.com/hoshi/".into()),
+            self_url: Some("https://example.com/hoshi/feed.xml".into()),
+            hub_url: Some("https://hub.example.com/".into()),
+            ..FeedOptions::default()
+        };
+        let xml = build_feed(&[row("acme/widget", 9, 10, 1)], Utc::now(), &options).unwrap();
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+        assert_eq!(channel.link(), "https://example.com/hoshi/");
+        let links = channel.atom_ext().unwrap().links();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].rel(), "self");
+        assert_eq!(links[0].href(), "https://example.com/hoshi/feed.xml");
+        assert_eq!(links[1].rel(), "hub");
+        assert_eq!(links[1].href(), "https://hub.example.com/");
+
+        let xml = build_feed(&[], Utc::now(), &FeedOptions::default()).unwrap();
+        let channel = rss::Channel::read_from(xml.as_bytes()).unwrap();
+        assert_eq!(channel.link(), CHANNEL_LINK);
+        assert!(channel.atom_ext().is_none());
+    }
 }
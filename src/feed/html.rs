@@ -0,0 +1,204 @@
+//! The dashboard page, bundled from `frontend/` by `build.rs`. Only built with
+//! the `frontend` feature so feed-only consumers skip the bundling step.
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use html_escape::encode_text;
+
+use crate::db::StarFeedRow;
+
+/// Renders the dashboard. With `display_tz` the page's timestamps are shown
+/// in that zone; otherwise "Last updated" is UTC and the rest follow the browser.
+pub fn build_html(
+    _events: &[StarFeedRow],
+    generated_at: DateTime<Utc>,
+    base_path: &str,
+    display_tz: Option<Tz>,
+) -> String {
+    let values = PageValues::new(generated_at, base_path, display_tz);
+    if cfg!(debug_assertions)
+        && let Some(html) = try_build_html_from_disk(&values)
+    {
+        return html;
+    }
+    build_html_from_embedded(&values)
+}
+
+fn build_html_from_embedded(values: &PageValues) -> String {
+    static EMBEDDED_TEMPLATE: &str = include_str!(concat!(env!("OUT_DIR"), "/frontend_index.html"));
+    static PARSED: OnceLock<PageTemplate> = OnceLock::new();
+    PARSED
+        .get_or_init(|| PageTemplate::parse(EMBEDDED_TEMPLATE))
+        .render(values)
+}
+
+/// The escaped text substituted for each placeholder.
+struct PageValues {
+    last_updated: String,
+    base_path: String,
+    display_tz: String,
+}
+
+impl PageValues {
+    fn new(generated_at: DateTime<Utc>, base_path: &str, display_tz: Option<Tz>) -> Self {
+        let last_updated = match display_tz {
+            Some(tz) => generated_at.with_timezone(&tz).to_rfc3339(),
+            None => generated_at.to_rfc3339(),
+        };
+        Self {
+            last_updated: encode_text(&last_updated).into_owned(),
+            base_path: encode_text(base_path).into_owned(),
+            display_tz: display_tz
+                .map(|tz| tz.name().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A page split around its placeholders once, so rendering only concatenates
+/// the pieces instead of rescanning the whole document per request.
+struct PageTemplate {
+    pieces: Vec<TemplatePiece>,
+    static_len: usize,
+}
+
+#[derive(Clone, Copy)]
+enum TemplatePiece {
+    Text(&'static str),
+    LastUpdated,
+    BasePath,
+    DisplayTz,
+}
+
+impl PageTemplate {
+    const PLACEHOLDERS: [(&'static str, TemplatePiece); 3] = [
+        ("__LAST_UPDATED__", TemplatePiece::LastUpdated),
+        ("__BASE_PATH__", TemplatePiece::BasePath),
+        ("__DISPLAY_TZ__", TemplatePiece::DisplayTz),
+    ];
+
+    fn parse(template: &'static str) -> Self {
+        let mut pieces = Vec::new();
+        let mut rest = template;
+        while let Some((index, placeholder, piece)) = Self::PLACEHOLDERS
+            .iter()
+            .filter_map(|(placeholder, piece)| {
+                rest.find(placeholder)
+                    .map(|index| (index, placeholder, piece))
+            })
+            .min_by_key(|(index, _, _)| *index)
+        {
+            if index > 0 {
+                pieces.push(TemplatePiece::Text(&rest[..index]));
+            }
+            pieces.push(*piece);
+            rest = &rest[index + placeholder.len()..];
+        }
+        if !rest.is_empty() {
+            pieces.push(TemplatePiece::Text(rest));
+        }
+        let static_len = pieces
+            .iter()
+            .map(|piece| match piece {
+                TemplatePiece::Text(text) => text.len(),
+                _ => 0,
+            })
+            .sum();
+        Self { pieces, static_len }
+    }
+
+    fn render(&self, values: &PageValues) -> String {
+        let mut html = String::with_capacity(
+            self.static_len
+                + values.last_updated.len()
+                + values.base_path.len()
+                + values.display_tz.len(),
+        );
+        for piece in &self.pieces {
+            html.push_str(match piece {
+                TemplatePiece::Text(text) => text,
+                TemplatePiece::LastUpdated => &values.last_updated,
+                TemplatePiece::BasePath => &values.base_path,
+                TemplatePiece::DisplayTz => &values.display_tz,
+            });
+        }
+        html
+    }
+}
+
+fn try_build_html_from_disk(values: &PageValues) -> Option<String> {
+    let frontend_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("frontend");
+    match render_from_dir(&frontend_dir, values) {
+        Ok(html) => Some(html),
+        Err(err) => {
+            eprintln!("hoshiyomi: falling back to embedded frontend ({err:#})");
+            None
+        }
+    }
+}
+
+/// Renders the page from `index.html`, `styles.css`, and `app.js` in `dir`,
+/// re-reading them on every call so edits show up on reload.
+pub fn build_html_from_dir(
+    dir: &Path,
+    generated_at: DateTime<Utc>,
+    base_path: &str,
+    display_tz: Option<Tz>,
+) -> Result<String> {
+    render_from_dir(dir, &PageValues::new(generated_at, base_path, display_tz))
+}
+
+fn render_from_dir(dir: &Path, values: &PageValues) -> Result<String> {
+    let read = |name: &str| {
+        let path = dir.join(name);
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))
+    };
+    let template = read("index.html")?;
+    let styles = read("styles.css")?;
+    let script = read("app.js")?;
+
+    let bundled = template
+        .replace("{{STYLE}}", styles.trim())
+        .replace("{{SCRIPT}}", script.trim());
+
+    Ok(bundled
+        .replace("__LAST_UPDATED__", &values.last_updated)
+        .replace("__BASE_PATH__", &values.base_path)
+        .replace("__DISPLAY_TZ__", &values.display_tz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn page_template_matches_string_replace() {
+        let template = "<p>__BASE_PATH__/api</p><time>__LAST_UPDATED__</time><a href=\"__BASE_PATH__\" data-tz=\"__DISPLAY_TZ__\">";
+        let generated_at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let values = PageValues::new(generated_at, "/hoshi", None);
+        let rendered = PageTemplate::parse(template).render(&values);
+        let expected = template
+            .replace("__LAST_UPDATED__", "2025-01-01T00:00:00+00:00")
+            .replace("__BASE_PATH__", "/hoshi")
+            .replace("__DISPLAY_TZ__", "");
+        assert_eq!(rendered, expected);
+
+        let plain = PageTemplate::parse("no placeholders");
+        assert_eq!(plain.render(&values), "no placeholders");
+    }
+
+    #[test]
+    fn display_tz_localizes_last_updated() {
+        let generated_at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let values = PageValues::new(generated_at, "", Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(values.last_updated, "2025-01-01T09:00:00+09:00");
+        assert_eq!(values.display_tz, "Asia/Tokyo");
+    }
+}
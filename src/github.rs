@@ -1,15 +1,27 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, TimeZone, Utc};
+use futures::StreamExt;
 use reqwest::{Client, StatusCode, Url, header};
 use serde::Deserialize;
 use thiserror::Error;
+use tokio::sync::OnceCell;
 
-use crate::config::Config;
+use crate::config::{Config, ProxySetting};
 
-const PER_PAGE: usize = 100;
+mod array_stream;
+
+use array_stream::ArrayElements;
+
+pub(crate) const PER_PAGE: usize = 100;
+/// Tries per request when GitHub cannot be reached (connection refused, DNS
+/// failure, timeout), e.g. while a laptop's network comes back after sleep.
+const CONNECT_ATTEMPTS: u32 = 4;
+/// Wait before the first retry; doubled for each later one.
+const DEFAULT_CONNECT_RETRY_DELAY: Duration = Duration::from_secs(2);
 const STAR_ACCEPT_HEADER: &str =
     "application/vnd.github.star+json, application/vnd.github.mercy-preview+json";
 
@@ -18,12 +30,26 @@ pub struct GitHubClient {
     client: Client,
     base_url: Url,
     rate_limit: Arc<RateLimitState>,
+    conditional_requests: bool,
+    /// `--max-response-bytes`: bodies past this size fail instead of being buffered.
+    max_response_bytes: u64,
+    authenticated_user: OnceCell<FollowingUser>,
+    resolved_users: Arc<Mutex<HashMap<String, ApiUser>>>,
+    connect_retry_delay: Duration,
+    /// `--max-pages-per-user`: newest-first paging stops after this many pages.
+    max_pages: Option<usize>,
+    /// `--max-concurrency`, reused for batched login lookups.
+    max_concurrency: usize,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct RateLimitSnapshot {
     pub remaining: Option<u32>,
     pub reset_at: Option<DateTime<Utc>>,
+    /// Requests spent in the current window, from `x-ratelimit-used`.
+    pub used: Option<u32>,
+    /// Which budget the last response counted against, e.g. `core` or `search`.
+    pub resource: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -37,6 +63,20 @@ pub struct FollowingUser {
     pub login: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct FollowingsPage {
+    pub users: Vec<FollowingUser>,
+    /// Last page advertised by the `Link` header, when GitHub sent one.
+    pub last_page: Option<usize>,
+}
+
+impl FollowingsPage {
+    /// Whether another page may follow when no `Link` header was available.
+    pub fn is_full(&self) -> bool {
+        self.users.len() >= PER_PAGE
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StarEvent {
     pub repo_full_name: String,
@@ -45,18 +85,41 @@ pub struct StarEvent {
     pub starred_at: DateTime<Utc>,
     pub repo_language: Option<String>,
     pub repo_topics: Vec<String>,
+    pub repo_owner_avatar_url: Option<String>,
+    pub repo_pushed_at: Option<DateTime<Utc>>,
+    /// GitHub omitted `starred_at`, so `starred_at` holds the fetch time instead.
+    pub starred_at_estimated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StarredPage {
+    pub events: Vec<StarEvent>,
+    /// GitHub's `X-Poll-Interval` for this request, if any.
+    pub poll_interval: Option<Duration>,
+}
+
+impl StarredPage {
+    /// Whether another page may follow.
+    pub fn is_full(&self) -> bool {
+        self.events.len() >= PER_PAGE
+    }
 }
 
 #[derive(Debug)]
 pub enum StarFetchOutcome {
     NotModified {
         fetched_at: DateTime<Utc>,
+        poll_interval: Option<Duration>,
     },
     Modified {
         fetched_at: DateTime<Utc>,
         etag: Option<String>,
         last_modified: Option<String>,
         events: Vec<StarEvent>,
+        /// Longest `X-Poll-Interval` GitHub sent while paging, if any.
+        poll_interval: Option<Duration>,
+        /// Paging stopped at `--max-pages-per-user` with older stars still unread.
+        truncated: bool,
     },
 }
 
@@ -68,20 +131,43 @@ pub enum GitHubApiError {
     Auth,
     #[error("access forbidden")]
     Forbidden,
+    #[error("not found")]
+    NotFound,
+    #[error("unexpected status {status}: {body}")]
+    Unexpected { status: StatusCode, body: String },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 struct ApiUser {
     login: String,
     id: i64,
+    /// Profile name; only the single-user endpoints include it.
+    #[serde(default)]
+    name: Option<String>,
 }
 
+/// An entry of `GET /users/{login}/starred`. With the `star+json` media type
+/// each repository is wrapped with its `starred_at`; if that negotiation fails
+/// GitHub answers with bare repositories instead.
 #[derive(Debug, Deserialize)]
-struct ApiStarredRepo {
-    starred_at: DateTime<Utc>,
-    repo: ApiRepo,
+#[serde(untagged)]
+enum ApiStarredRepo {
+    Starred {
+        starred_at: Option<DateTime<Utc>>,
+        repo: ApiRepo,
+    },
+    Bare(ApiRepo),
+}
+
+impl ApiStarredRepo {
+    fn into_parts(self) -> (Option<DateTime<Utc>>, ApiRepo) {
+        match self {
+            ApiStarredRepo::Starred { starred_at, repo } => (starred_at, repo),
+            ApiStarredRepo::Bare(repo) => (None, repo),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -92,6 +178,13 @@ struct ApiRepo {
     language: Option<String>,
     #[serde(default)]
     topics: Vec<String>,
+    owner: Option<ApiOwner>,
+    pushed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiOwner {
+    avatar_url: Option<String>,
 }
 
 impl GitHubClient {
@@ -99,8 +192,11 @@ impl GitHubClient {
         let mut default_headers = header::HeaderMap::new();
         default_headers.insert(
             header::USER_AGENT,
-            header::HeaderValue::from_str(&config.user_agent)
-                .context("invalid user agent header value")?,
+            header::HeaderValue::from_str(&user_agent(
+                &config.user_agent,
+                config.contact.as_deref(),
+            ))
+            .context("invalid user agent header value")?,
         );
         default_headers.insert(
             header::ACCEPT,
@@ -112,9 +208,8 @@ impl GitHubClient {
             header::HeaderValue::from_str(&bearer).context("invalid token header value")?,
         );
 
-        let client = Client::builder()
+        let client = http_client_builder(config)?
             .default_headers(default_headers)
-            .timeout(Duration::from_secs(config.timeout_secs))
             .build()
             .context("failed to build reqwest client")?;
 
@@ -122,63 +217,231 @@ impl GitHubClient {
             client,
             base_url: config.api_base_url.clone(),
             rate_limit: Arc::new(RateLimitState::default()),
+            authenticated_user: OnceCell::new(),
+            resolved_users: Arc::new(Mutex::new(HashMap::new())),
+            conditional_requests: !config.no_conditional,
+            max_response_bytes: config.max_response_bytes,
+            connect_retry_delay: DEFAULT_CONNECT_RETRY_DELAY,
+            max_pages: config.max_pages_per_user,
+            max_concurrency: config.max_concurrency.max(1),
         })
     }
 
+    /// Replaces the first backoff between retries of unreachable requests.
+    pub fn with_connect_retry_delay(mut self, delay: Duration) -> Self {
+        self.connect_retry_delay = delay;
+        self
+    }
+
+    /// Sends `request`, retrying with exponential backoff while GitHub cannot be
+    /// reached. Other failures, and connection errors past the last attempt, are
+    /// returned as they are.
+    async fn send(
+        &self,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, GitHubApiError> {
+        let mut delay = self.connect_retry_delay;
+        let mut attempt = 1;
+        loop {
+            let retry = (attempt < CONNECT_ATTEMPTS)
+                .then(|| request.try_clone())
+                .flatten();
+            match request.send().await {
+                Ok(response) => return Ok(response),
+                Err(err) if err.is_connect() || err.is_timeout() => match retry {
+                    Some(next) => {
+                        eprintln!(
+                            "Could not reach GitHub ({err}); retrying in {}s (attempt {attempt}/{CONNECT_ATTEMPTS})",
+                            delay.as_secs_f32()
+                        );
+                        tokio::time::sleep(delay).await;
+                        delay *= 2;
+                        attempt += 1;
+                        request = next;
+                    }
+                    None => {
+                        return Err(anyhow!(err)
+                            .context(format!("GitHub unreachable after {attempt} attempts"))
+                            .into());
+                    }
+                },
+                Err(err) => return Err(anyhow!(err).into()),
+            }
+        }
+    }
+
     pub async fn fetch_followings(&self) -> Result<Vec<FollowingUser>, GitHubApiError> {
         let mut results = Vec::new();
         let mut page = 1usize;
         loop {
-            let mut url = self
-                .base_url
-                .join("user/following")
-                .map_err(|e| anyhow!(e))?;
-            url.query_pairs_mut()
-                .append_pair("per_page", &PER_PAGE.to_string())
-                .append_pair("page", &page.to_string());
-
-            let response = self.client.get(url).send().await.map_err(|e| anyhow!(e))?;
-            self.rate_limit.update(response.headers());
-            match response.status() {
-                StatusCode::OK => {
-                    let body: Vec<ApiUser> = response
-                        .json()
-                        .await
-                        .map_err(|e| anyhow!("failed to parse followings: {e}"))?;
-                    let page_len = body.len();
-                    if page_len == 0 {
-                        break;
-                    }
-                    for user in body {
-                        results.push(FollowingUser {
-                            id: user.id,
-                            login: user.login,
-                        });
-                    }
-                    if page_len < PER_PAGE {
-                        break;
-                    }
-                    page += 1;
-                }
-                StatusCode::UNAUTHORIZED => return Err(GitHubApiError::Auth),
-                StatusCode::FORBIDDEN => {
-                    if let Some(wait) = parse_retry_after(&response) {
-                        return Err(GitHubApiError::RateLimited(wait));
-                    }
-                    return Err(GitHubApiError::Forbidden);
-                }
-                other => {
-                    let text = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "<unavailable>".to_string());
-                    return Err(anyhow!("unexpected status {other}: {text}").into());
-                }
+            let batch = self.fetch_followings_page(page).await?;
+            let has_more = match batch.last_page {
+                Some(last) => page < last,
+                None => batch.is_full(),
+            };
+            results.extend(batch.users);
+            if !has_more {
+                break;
             }
+            page += 1;
         }
         Ok(results)
     }
 
+    /// The user the token belongs to, looked up once per client.
+    pub async fn authenticated_user(&self) -> Result<FollowingUser, GitHubApiError> {
+        self.authenticated_user
+            .get_or_try_init(|| self.fetch_user("user"))
+            .await
+            .cloned()
+    }
+
+    /// Resolves a login to its account id, remembering the answer for later polls.
+    pub async fn lookup_user(&self, login: &str) -> Result<FollowingUser, GitHubApiError> {
+        let user = self.resolve_user(login).await?;
+        Ok(FollowingUser {
+            id: user.id,
+            login: user.login,
+        })
+    }
+
+    /// Resolves many logins at once, up to `--max-concurrency` lookups in
+    /// flight. Logins GitHub doesn't know are left out; the rest keep their
+    /// input order. Any other failure ends the batch, naming the login it hit.
+    pub async fn resolve_logins(&self, logins: &[String]) -> Result<Vec<FollowingUser>> {
+        // Owned logins keep the stream `Send` for callers inside `tokio::spawn`.
+        let lookups: Vec<_> = futures::stream::iter(logins.to_vec())
+            .map(|login| async move {
+                let lookup = self.lookup_user(&login).await;
+                (login, lookup)
+            })
+            .buffered(self.max_concurrency)
+            .collect()
+            .await;
+        let mut users = Vec::with_capacity(lookups.len());
+        for (login, lookup) in lookups {
+            match lookup {
+                Ok(user) => users.push(user),
+                Err(GitHubApiError::NotFound) => {}
+                Err(err) => return Err(anyhow!(err).context(login)),
+            }
+        }
+        Ok(users)
+    }
+
+    /// The profile name `login` shows on GitHub, which the followings list
+    /// leaves out. `None` when the account has not set one.
+    pub async fn fetch_display_name(&self, login: &str) -> Result<Option<String>, GitHubApiError> {
+        let user = self.resolve_user(login).await?;
+        Ok(user
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()))
+    }
+
+    async fn resolve_user(&self, login: &str) -> Result<ApiUser, GitHubApiError> {
+        let key = login.to_ascii_lowercase();
+        let cached = self
+            .resolved_users
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .get(&key)
+            .cloned();
+        if let Some(user) = cached {
+            return Ok(user);
+        }
+        let user = self.fetch_api_user(&format!("users/{login}")).await?;
+        self.resolved_users
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+            .insert(key, user.clone());
+        Ok(user)
+    }
+
+    async fn fetch_user(&self, path: &str) -> Result<FollowingUser, GitHubApiError> {
+        let user = self.fetch_api_user(path).await?;
+        Ok(FollowingUser {
+            id: user.id,
+            login: user.login,
+        })
+    }
+
+    async fn fetch_api_user(&self, path: &str) -> Result<ApiUser, GitHubApiError> {
+        let url = self.base_url.join(path).map_err(|e| anyhow!(e))?;
+        let response = self.send(self.client.get(url)).await?;
+        self.rate_limit.update(response.headers());
+        match response.status() {
+            StatusCode::OK => {
+                let body = read_body(response, self.max_response_bytes, "user").await?;
+                let user: ApiUser = serde_json::from_slice(&body)
+                    .map_err(|e| anyhow!("failed to parse user: {e}"))?;
+                Ok(user)
+            }
+            StatusCode::UNAUTHORIZED => Err(GitHubApiError::Auth),
+            StatusCode::NOT_FOUND => Err(GitHubApiError::NotFound),
+            StatusCode::FORBIDDEN => Err(forbidden(&response)),
+            _ => Err(unexpected_status(response, self.max_response_bytes).await),
+        }
+    }
+
+    pub async fn fetch_followings_page(
+        &self,
+        page: usize,
+    ) -> Result<FollowingsPage, GitHubApiError> {
+        self.fetch_users_page("user/following", "followings", page)
+            .await
+    }
+
+    /// One page of an organization's members. Private members are only listed
+    /// when the token belongs to a member of `org`.
+    pub async fn fetch_org_members_page(
+        &self,
+        org: &str,
+        page: usize,
+    ) -> Result<FollowingsPage, GitHubApiError> {
+        self.fetch_users_page(&format!("orgs/{org}/members"), "org members", page)
+            .await
+    }
+
+    async fn fetch_users_page(
+        &self,
+        path: &str,
+        what: &str,
+        page: usize,
+    ) -> Result<FollowingsPage, GitHubApiError> {
+        let mut url = self.base_url.join(path).map_err(|e| anyhow!(e))?;
+        url.query_pairs_mut()
+            .append_pair("per_page", &PER_PAGE.to_string())
+            .append_pair("page", &page.to_string());
+
+        let response = self.send(self.client.get(url)).await?;
+        self.rate_limit.update(response.headers());
+        match response.status() {
+            StatusCode::OK => {
+                let last_page = response
+                    .headers()
+                    .get(header::LINK)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_last_page);
+                let body = read_body(response, self.max_response_bytes, what).await?;
+                let body: Vec<ApiUser> = serde_json::from_slice(&body)
+                    .map_err(|e| anyhow!("failed to parse {what}: {e}"))?;
+                let users = body
+                    .into_iter()
+                    .map(|user| FollowingUser {
+                        id: user.id,
+                        login: user.login,
+                    })
+                    .collect();
+                Ok(FollowingsPage { users, last_page })
+            }
+            StatusCode::UNAUTHORIZED => Err(GitHubApiError::Auth),
+            StatusCode::NOT_FOUND => Err(GitHubApiError::NotFound),
+            StatusCode::FORBIDDEN => Err(forbidden(&response)),
+            _ => Err(unexpected_status(response, self.max_response_bytes).await),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn fetch_starred(
         &self,
@@ -193,19 +456,24 @@ impl GitHubClient {
         let mut newest_last_modified: Option<String> = None;
         let mut first_request = true;
         let mut continue_paging = true;
+        let mut truncated = false;
+        let mut poll_interval: Option<Duration> = None;
 
         while continue_paging {
             let mut url = self
                 .base_url
                 .join(&format!("users/{login}/starred"))
                 .map_err(|e| anyhow!(e))?;
+            // The `known_latest` cutoff below assumes newest-first pages, so ask for it explicitly.
             url.query_pairs_mut()
+                .append_pair("sort", "created")
+                .append_pair("direction", "desc")
                 .append_pair("per_page", &PER_PAGE.to_string())
                 .append_pair("page", &page.to_string());
 
             let mut request = self.client.get(url);
             request = request.header(header::ACCEPT, STAR_ACCEPT_HEADER);
-            if first_request {
+            if first_request && self.conditional_requests {
                 if let Some(tag) = etag {
                     request = request.header(header::IF_NONE_MATCH, tag);
                 }
@@ -214,12 +482,13 @@ impl GitHubClient {
                 }
             }
 
-            let response = request.send().await.map_err(|e| anyhow!(e))?;
+            let response = self.send(request).await?;
             self.rate_limit.update(response.headers());
+            poll_interval = poll_interval.max(parse_poll_interval(response.headers()));
             match response.status() {
                 StatusCode::OK => {
                     let headers = response.headers().clone();
-                    if first_request {
+                    if first_request && self.conditional_requests {
                         newest_etag = headers
                             .get(header::ETAG)
                             .and_then(|h| h.to_str().ok())
@@ -229,58 +498,57 @@ impl GitHubClient {
                             .and_then(|h| h.to_str().ok())
                             .map(ToOwned::to_owned);
                     }
-                    let body: Vec<ApiStarredRepo> = response
-                        .json()
-                        .await
-                        .map_err(|e| anyhow!("failed to parse starred repos: {e}"))?;
-                    if body.is_empty() {
-                        break;
-                    }
-                    let mut page_new_events = Vec::new();
-                    for item in body {
+                    let page_events = read_starred_page(response, self.max_response_bytes).await?;
+                    let page_len = page_events.len();
+                    let missing_starred_at = page_events
+                        .iter()
+                        .filter(|event| event.starred_at_estimated)
+                        .count();
+                    for event in page_events {
+                        // Skip rather than stop so a slightly out-of-order page still
+                        // yields every newer star; paging ends after this page.
                         if let Some(latest) = known_latest
-                            && item.starred_at <= latest
+                            && event.starred_at <= latest
                         {
                             continue_paging = false;
-                            break;
+                            continue;
                         }
-                        page_new_events.push(StarEvent {
-                            repo_full_name: item.repo.full_name,
-                            repo_description: item.repo.description,
-                            repo_html_url: item.repo.html_url,
-                            starred_at: item.starred_at,
-                            repo_language: item.repo.language,
-                            repo_topics: item.repo.topics,
-                        });
+                        events.push(event);
+                    }
+                    if page_len == 0 {
+                        break;
+                    }
+                    if missing_starred_at > 0 {
+                        // Without star times the cutoff cannot work, so don't walk the
+                        // whole history; the next poll will usually negotiate properly.
+                        eprintln!(
+                            "GitHub returned {missing_starred_at} starred repos for {login} without starred_at; using the fetch time"
+                        );
+                        continue_paging = false;
                     }
-                    let added_count = page_new_events.len();
-                    events.extend(page_new_events);
                     if !continue_paging {
                         break;
                     }
-                    if added_count < PER_PAGE {
+                    if page_len < PER_PAGE {
+                        break;
+                    }
+                    if self.max_pages.is_some_and(|max| page >= max) {
+                        truncated = true;
                         break;
                     }
                     page += 1;
                 }
                 StatusCode::NOT_MODIFIED => {
                     let fetched_at = Utc::now();
-                    return Ok(StarFetchOutcome::NotModified { fetched_at });
+                    return Ok(StarFetchOutcome::NotModified {
+                        fetched_at,
+                        poll_interval,
+                    });
                 }
+                StatusCode::NOT_FOUND => return Err(GitHubApiError::NotFound),
                 StatusCode::UNAUTHORIZED => return Err(GitHubApiError::Auth),
-                StatusCode::FORBIDDEN => {
-                    if let Some(wait) = parse_retry_after(&response) {
-                        return Err(GitHubApiError::RateLimited(wait));
-                    }
-                    return Err(GitHubApiError::Forbidden);
-                }
-                other => {
-                    let text = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "<unavailable>".to_string());
-                    return Err(anyhow!("unexpected status {other}: {text}").into());
-                }
+                StatusCode::FORBIDDEN => return Err(forbidden(&response)),
+                _ => return Err(unexpected_status(response, self.max_response_bytes).await),
             }
             first_request = false;
         }
@@ -291,9 +559,49 @@ impl GitHubClient {
             etag: newest_etag,
             last_modified: newest_last_modified,
             events,
+            poll_interval,
+            truncated,
         })
     }
 
+    /// Fetches one page of a user's stars oldest-first, for `--backfill-ascending`.
+    /// No validators are sent: the pages are walked once and committed as they arrive.
+    pub async fn fetch_starred_page_ascending(
+        &self,
+        login: &str,
+        page: usize,
+    ) -> Result<StarredPage, GitHubApiError> {
+        let mut url = self
+            .base_url
+            .join(&format!("users/{login}/starred"))
+            .map_err(|e| anyhow!(e))?;
+        url.query_pairs_mut()
+            .append_pair("sort", "created")
+            .append_pair("direction", "asc")
+            .append_pair("per_page", &PER_PAGE.to_string())
+            .append_pair("page", &page.to_string());
+
+        let response = self
+            .send(
+                self.client
+                    .get(url)
+                    .header(header::ACCEPT, STAR_ACCEPT_HEADER),
+            )
+            .await?;
+        self.rate_limit.update(response.headers());
+        let poll_interval = parse_poll_interval(response.headers());
+        match response.status() {
+            StatusCode::OK => Ok(StarredPage {
+                events: read_starred_page(response, self.max_response_bytes).await?,
+                poll_interval,
+            }),
+            StatusCode::NOT_FOUND => Err(GitHubApiError::NotFound),
+            StatusCode::UNAUTHORIZED => Err(GitHubApiError::Auth),
+            StatusCode::FORBIDDEN => Err(forbidden(&response)),
+            _ => Err(unexpected_status(response, self.max_response_bytes).await),
+        }
+    }
+
     pub fn rate_limit_snapshot(&self) -> RateLimitSnapshot {
         self.rate_limit.snapshot()
     }
@@ -319,16 +627,205 @@ impl RateLimitState {
         {
             guard.reset_at = Utc.timestamp_opt(reset, 0).single();
         }
+        if let Some(used) = headers
+            .get("x-ratelimit-used")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|raw| raw.parse::<u32>().ok())
+        {
+            guard.used = Some(used);
+        }
+        if let Some(resource) = headers
+            .get("x-ratelimit-resource")
+            .and_then(|value| value.to_str().ok())
+        {
+            guard.resource = Some(resource.to_string());
+        }
     }
 
     fn snapshot(&self) -> RateLimitSnapshot {
-        *self
-            .inner
+        self.inner
             .lock()
             .unwrap_or_else(|poison| poison.into_inner())
+            .clone()
     }
 }
 
+/// Reads `X-Poll-Interval`, GitHub's minimum spacing between polls in seconds.
+fn parse_poll_interval(headers: &header::HeaderMap) -> Option<Duration> {
+    headers
+        .get("x-poll-interval")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Classifies a 403. Rate-limit hits are logged with the headers GitHub
+/// support asks for, so the exhausted budget can be traced on their side.
+fn forbidden(response: &reqwest::Response) -> GitHubApiError {
+    let retry_after = parse_retry_after(response);
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("<none>")
+    };
+    if retry_after.is_some() || header("x-ratelimit-remaining") == "0" {
+        eprintln!(
+            "GitHub rate limit hit for {} (resource: {}, used: {}, remaining: {}, x-github-request-id: {})",
+            response.url(),
+            header("x-ratelimit-resource"),
+            header("x-ratelimit-used"),
+            header("x-ratelimit-remaining"),
+            request_id(response)
+        );
+    }
+    match retry_after {
+        Some(wait) => GitHubApiError::RateLimited(wait),
+        None => GitHubApiError::Forbidden,
+    }
+}
+
+fn request_id(response: &reqwest::Response) -> String {
+    response
+        .headers()
+        .get("x-github-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("<none>")
+        .to_string()
+}
+
+async fn unexpected_status(response: reqwest::Response, limit: u64) -> GitHubApiError {
+    let status = response.status();
+    let request_id = request_id(&response);
+    let url = response.url().clone();
+    let body = read_body(response, limit, "error")
+        .await
+        .map(|body| String::from_utf8_lossy(&body).into_owned())
+        .unwrap_or_else(|_| "<unavailable>".to_string());
+    eprintln!("GitHub returned {status} for {url} (x-github-request-id: {request_id})");
+    GitHubApiError::Unexpected { status, body }
+}
+
+/// Parses one page of `/users/{login}/starred`. Large backfill pages are parsed
+/// element by element as they arrive rather than buffered and deserialized in
+/// one go. Stars without `starred_at` get the fetch time and are marked estimated.
+async fn read_starred_page(
+    response: reqwest::Response,
+    limit: u64,
+) -> Result<Vec<StarEvent>, GitHubApiError> {
+    let page_fetched_at = Utc::now();
+    let mut events = Vec::new();
+    check_content_length(&response, limit, "starred repos")?;
+    let mut elements = ArrayElements::default();
+    let mut received = 0u64;
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| anyhow!("failed to read starred repos: {e}"))?;
+        received += chunk.len() as u64;
+        if received > limit {
+            return Err(too_large("starred repos", limit).into());
+        }
+        elements
+            .feed(&chunk, |raw| {
+                let item: ApiStarredRepo = serde_json::from_slice(raw)?;
+                let (starred_at, repo) = item.into_parts();
+                events.push(StarEvent {
+                    repo_full_name: repo.full_name,
+                    repo_description: repo.description,
+                    repo_html_url: repo.html_url,
+                    starred_at: starred_at.unwrap_or(page_fetched_at),
+                    repo_language: repo.language,
+                    repo_topics: repo.topics,
+                    repo_owner_avatar_url: repo.owner.and_then(|owner| owner.avatar_url),
+                    repo_pushed_at: repo.pushed_at,
+                    starred_at_estimated: starred_at.is_none(),
+                });
+                Ok(())
+            })
+            .map_err(|e| anyhow!("failed to parse starred repos: {e}"))?;
+    }
+    elements
+        .finish()
+        .map_err(|e| anyhow!("failed to parse starred repos: {e}"))?;
+    Ok(events)
+}
+
+/// Buffers a whole body, giving up as soon as it passes `limit` bytes so a
+/// hostile `api_base_url` cannot exhaust memory.
+async fn read_body(response: reqwest::Response, limit: u64, what: &str) -> Result<Vec<u8>> {
+    check_content_length(&response, limit, what)?;
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| anyhow!("failed to read {what}: {e}"))?;
+        if (body.len() + chunk.len()) as u64 > limit {
+            return Err(too_large(what, limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Rejects a response up front when its `Content-Length` is already over `limit`.
+fn check_content_length(response: &reqwest::Response, limit: u64, what: &str) -> Result<()> {
+    match response.content_length() {
+        Some(len) if len > limit => Err(too_large(what, limit)),
+        _ => Ok(()),
+    }
+}
+
+fn too_large(what: &str, limit: u64) -> anyhow::Error {
+    anyhow!("{what} response is larger than --max-response-bytes ({limit} bytes)")
+}
+
+/// Timeouts and proxy settings shared by every outbound HTTP client.
+pub(crate) fn http_client_builder(config: &Config) -> Result<reqwest::ClientBuilder> {
+    let builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs));
+    Ok(match &config.proxy {
+        ProxySetting::System => builder,
+        ProxySetting::Explicit(url) => {
+            builder.proxy(reqwest::Proxy::all(url.as_str()).context("invalid proxy url")?)
+        }
+        ProxySetting::Disabled => builder.no_proxy(),
+    })
+}
+
+/// Builds the User-Agent sent to GitHub: a bare product name gets the crate
+/// version appended, and a configured contact is added as a comment.
+pub(crate) fn user_agent(product: &str, contact: Option<&str>) -> String {
+    let mut ua = product.trim().to_string();
+    if !ua.contains('/') {
+        ua.push('/');
+        ua.push_str(env!("CARGO_PKG_VERSION"));
+    }
+    if let Some(contact) = contact {
+        ua.push_str(&format!(" (+{contact})"));
+    }
+    ua
+}
+
+/// Extracts the `page` number of the `rel="last"` entry from a GitHub `Link` header.
+fn parse_last_page(link: &str) -> Option<usize> {
+    link.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        if !params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"last\"")
+        {
+            return None;
+        }
+        let target = target.trim().trim_start_matches('<').trim_end_matches('>');
+        let url = Url::parse(target).ok()?;
+        url.query_pairs()
+            .find(|(key, _)| key == "page")
+            .and_then(|(_, value)| value.parse().ok())
+    })
+}
+
 fn parse_retry_after(response: &reqwest::Response) -> Option<Duration> {
     response
         .headers()
@@ -337,3 +834,32 @@ fn parse_retry_after(response: &reqwest::Response) -> Option<Duration> {
         .and_then(|s| s.parse::<u64>().ok())
         .map(Duration::from_secs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_agent_adds_version_and_contact() {
+        let version = env!("CARGO_PKG_VERSION");
+        assert_eq!(
+            user_agent("following-stars-rss", None),
+            format!("following-stars-rss/{version}")
+        );
+        assert_eq!(
+            user_agent("following-stars-rss", Some("ops@example.com")),
+            format!("following-stars-rss/{version} (+ops@example.com)")
+        );
+        assert_eq!(user_agent("my-bot/2.0", None), "my-bot/2.0");
+    }
+
+    #[test]
+    fn parses_last_page_from_link_header() {
+        let link = "<https://api.github.com/user/following?per_page=100&page=2>; rel=\"next\", <https://api.github.com/user/following?per_page=100&page=7>; rel=\"last\"";
+        assert_eq!(parse_last_page(link), Some(7));
+        assert_eq!(
+            parse_last_page("<https://api.github.com/user/following?page=1>; rel=\"prev\""),
+            None
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Splits a JSON array arriving in chunks into its elements, so a large page
+//! can be deserialized one element at a time instead of buffered whole.
+
+use anyhow::{Result, anyhow};
+
+/// Incremental scanner over a top-level JSON array of objects or arrays.
+/// Only the element currently being read is held in memory.
+#[derive(Debug, Default)]
+pub(super) struct ArrayElements {
+    element: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    opened: bool,
+    closed: bool,
+}
+
+impl ArrayElements {
+    /// Scans `chunk`, calling `on_element` with the raw bytes of every element
+    /// completed within it.
+    pub(super) fn feed(
+        &mut self,
+        chunk: &[u8],
+        mut on_element: impl FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        for &byte in chunk {
+            if !self.opened {
+                match byte {
+                    b'[' => self.opened = true,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err(anyhow!("expected a JSON array")),
+                }
+                continue;
+            }
+            if self.closed {
+                if !byte.is_ascii_whitespace() {
+                    return Err(anyhow!("unexpected data after the JSON array"));
+                }
+                continue;
+            }
+            if self.depth == 0 {
+                match byte {
+                    b']' => self.closed = true,
+                    b',' => {}
+                    b if b.is_ascii_whitespace() => {}
+                    b'{' | b'[' => {
+                        self.depth = 1;
+                        self.element.push(byte);
+                    }
+                    _ => return Err(anyhow!("expected an object or array element")),
+                }
+                continue;
+            }
+
+            self.element.push(byte);
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        on_element(&self.element)?;
+                        self.element.clear();
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if the body ended before the array was closed.
+    pub(super) fn finish(&self) -> Result<()> {
+        if self.closed {
+            Ok(())
+        } else {
+            Err(anyhow!("the JSON array ended early"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&str]) -> Result<Vec<String>> {
+        let mut scanner = ArrayElements::default();
+        let mut elements = Vec::new();
+        for chunk in chunks {
+            scanner.feed(chunk.as_bytes(), |raw| {
+                elements.push(String::from_utf8(raw.to_vec())?);
+                Ok(())
+            })?;
+        }
+        scanner.finish()?;
+        Ok(elements)
+    }
+
+    #[test]
+    fn elements_split_across_chunk_boundaries() {
+        let elements = split(&[
+            " [{\"a\":\"x}\\\"{\",",
+            "\"b\":[1,{\"c\":2}]}, ",
+            "{\"d\":\"\\\\\"}",
+            "]\n",
+        ])
+        .unwrap();
+        assert_eq!(
+            elements,
+            vec![
+                "{\"a\":\"x}\\\"{\",\"b\":[1,{\"c\":2}]}",
+                "{\"d\":\"\\\\\"}"
+            ]
+        );
+        assert!(split(&["[]"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_arrays_are_rejected() {
+        assert!(split(&["{\"a\":1}"]).is_err());
+        assert!(split(&["[{\"a\":1}"]).is_err());
+        assert!(split(&["[{\"a\":1}] x"]).is_err());
+        assert!(split(&["[1]"]).is_err());
+    }
+}
@@ -1,8 +1,12 @@
+pub mod clock;
 pub mod config;
 pub mod db;
+pub mod export;
 pub mod feed;
 pub mod github;
 pub mod pipeline;
 pub mod server;
+pub mod snapshot;
+pub mod websub;
 
 pub use config::Config;
@@ -1,7 +1,12 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use chrono::Utc;
 use hoshiyomi::Config;
 use hoshiyomi::config::Mode;
-use hoshiyomi::db::init;
+use hoshiyomi::db::{
+    configure_sqlite, import_database, init_with_tiers, recompute_all_users, reset_validators,
+    run_maintenance,
+};
+use hoshiyomi::export::export_stars;
 use hoshiyomi::github::GitHubClient;
 use hoshiyomi::pipeline::{build_feed_xml, poll_once};
 use hoshiyomi::server;
@@ -10,6 +15,7 @@ use std::sync::Arc;
 #[tokio::main]
 async fn main() -> Result<()> {
     let config = Config::from_cli()?;
+    configure_sqlite(config.sqlite);
     match &config.mode {
         Mode::Once => {
             let feed = run_once(&config).await?;
@@ -17,12 +23,67 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Mode::Serve(_) => server::run_server(config).await,
+        Mode::Recompute => {
+            init_with_tiers(&config.db_path, &config.activity_tiers).await?;
+            let updated = recompute_all_users(&config.db_path, &config).await?;
+            println!("Recomputed polling intervals for {updated} users");
+            Ok(())
+        }
+        Mode::Export(opts) => {
+            init_with_tiers(&config.db_path, &config.activity_tiers).await?;
+            let count = export_stars(&config.db_path, opts.format, opts.output.as_deref()).await?;
+            eprintln!("Exported {count} star events");
+            Ok(())
+        }
+        Mode::Maintenance => {
+            init_with_tiers(&config.db_path, &config.activity_tiers).await?;
+            let report = run_maintenance(&config.db_path).await?;
+            println!(
+                "Maintenance complete: {} -> {} bytes ({} freed), {} expired watermarks dropped, WAL checkpoint busy={}",
+                report.bytes_before,
+                report.bytes_after,
+                report.freed_bytes(),
+                report.watermarks_pruned,
+                report.checkpoint.busy
+            );
+            Ok(())
+        }
+        Mode::ResetEtag(login) => {
+            init_with_tiers(&config.db_path, &config.activity_tiers).await?;
+            if !reset_validators(&config.db_path, login, Utc::now()).await? {
+                return Err(anyhow!("no tracked user named {login}"));
+            }
+            println!(
+                "Cleared the cached ETag for {login}; the next poll will fetch their stars in full"
+            );
+            Ok(())
+        }
+        Mode::ImportDb(source) => {
+            init_with_tiers(&config.db_path, &config.activity_tiers).await?;
+            let report = import_database(&config.db_path, source).await?;
+            println!(
+                "Imported {} stars from {}: {} new users, {} merged into tracked ones",
+                report.stars_added,
+                source.display(),
+                report.users_added,
+                report.users_merged
+            );
+            Ok(())
+        }
+        Mode::Config(report) => {
+            if report.show {
+                print!("{}", report.render());
+            } else {
+                println!("Configuration is valid");
+            }
+            Ok(())
+        }
     }
 }
 
 async fn run_once(config: &Config) -> Result<String> {
-    init(&config.db_path).await?;
+    init_with_tiers(&config.db_path, &config.activity_tiers).await?;
     let client = Arc::new(GitHubClient::new(config)?);
-    poll_once(config, client).await?;
-    build_feed_xml(config).await
+    poll_once(Arc::new(config.clone()), client, None).await?;
+    build_feed_xml(config, Utc::now()).await
 }
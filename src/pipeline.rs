@@ -1,74 +1,618 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow};
-use chrono::Utc;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use futures::StreamExt;
-use tokio::sync::Semaphore;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use tokio::sync::{Notify, Semaphore};
 
 use crate::config::Config;
+use crate::db::star_query::{self, StarQuery, StarQueryStats};
+use crate::db::writer::StarWriter;
 use crate::db::{
-    UserRecord, defer_user, due_users, insert_star_events, recent_events_for_feed,
-    record_not_modified, upsert_followings,
+    StarBatch, UserRecord, apply_interval_pins, defer_user, due_users, followed_users,
+    has_any_stars, mark_unfollowed, recent_events_by_language, recent_events_by_tier,
+    recent_events_for_feed, record_backfill_page, record_display_name, record_not_modified,
+    record_user_not_found, upsert_followings, user_by_login, users_missing_display_name,
 };
 use crate::feed;
-use crate::github::{self, GitHubApiError, GitHubClient, StarFetchOutcome};
+use crate::github::{self, GitHubApiError, GitHubClient, RateLimitSnapshot, StarFetchOutcome};
 
-pub async fn poll_once(config: &Config, client: Arc<GitHubClient>) -> Result<()> {
-    let followings = fetch_followings_with_retry(client.clone()).await?;
-    upsert_followings(&config.db_path, &followings, config.max_interval_minutes).await?;
+/// Longest rate-limit reset the followings fetch will wait out; beyond this the
+/// poll is abandoned so the next scheduled poll can try again.
+const MAX_FOLLOWINGS_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+/// How often a long rate-limit wait reports the remaining time.
+const RATE_LIMIT_LOG_INTERVAL: Duration = Duration::from_secs(60);
+/// Minimum gap between backfill progress lines.
+const BACKFILL_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// Profile names looked up per poll, so a large following list fills in over
+/// several polls instead of spending its budget at once.
+const DISPLAY_NAME_BATCH: usize = 20;
 
-    let due = due_users(&config.db_path, Utc::now()).await?;
+/// How far a polling pass got before returning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    /// Every due user was processed.
+    Complete,
+    /// `--poll-deadline-secs` ran out, or the rate limit fell to
+    /// `--rate-limit-reserve`; `skipped` users were left for the next pass.
+    Partial { skipped: usize },
+}
+
+/// Runs one polling pass. `max_concurrency` bounds every GitHub request in the
+/// pass, including the followings pagination. When `shutdown` is notified, a
+/// pending rate-limit wait is abandoned. Once `poll_deadline_secs` elapses no
+/// further users are started; those already in flight run to completion.
+pub async fn poll_once(
+    config: Arc<Config>,
+    client: Arc<GitHubClient>,
+    shutdown: Option<Arc<Notify>>,
+) -> Result<PollOutcome> {
+    let deadline = config
+        .poll_deadline_secs
+        .map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
+    let semaphore = Arc::new(Semaphore::new(config.max_concurrency));
+    let mut followings =
+        fetch_followings_with_retry(client.clone(), semaphore.clone(), shutdown.clone()).await?;
+    // Unfollows are only inferred from a roster where every source resolved;
+    // a skipped org or login would otherwise retire all of its users.
+    let mut roster_complete = !followings.is_empty();
+    if config.include_self {
+        let me = client
+            .authenticated_user()
+            .await
+            .map_err(|err| anyhow!("failed to look up the authenticated user: {err}"))?;
+        if !followings.iter().any(|user| user.id == me.id) {
+            followings.push(me);
+        }
+    }
+    let tracked = client
+        .resolve_logins(&config.extra_users)
+        .await
+        .map_err(|err| anyhow!("failed to look up tracked user {err:#}"))?;
+    for login in &config.extra_users {
+        if !tracked
+            .iter()
+            .any(|user| user.login.eq_ignore_ascii_case(login))
+        {
+            eprintln!("Tracked user {login} not found on GitHub; skipping");
+            roster_complete = false;
+        }
+    }
+    for user in tracked {
+        if !followings.iter().any(|known| known.id == user.id) {
+            followings.push(user);
+        }
+    }
+    for org in &config.extra_orgs {
+        let members =
+            fetch_org_members_with_retry(client.clone(), semaphore.clone(), shutdown.clone(), org)
+                .await?;
+        let Some(members) = members else {
+            eprintln!("Tracked org {org} not found on GitHub; skipping");
+            roster_complete = false;
+            continue;
+        };
+        for member in members {
+            if !followings.iter().any(|known| known.id == member.id) {
+                followings.push(member);
+            }
+        }
+    }
+    // New users start at the slowest interval, so they belong to the slowest tier.
+    upsert_followings(
+        &config.db_path,
+        &followings,
+        config.max_interval_minutes,
+        config.activity_tiers.slowest(),
+    )
+    .await?;
+    if roster_complete && !config.keep_unfollowed {
+        let retired = mark_unfollowed(&config.db_path, &followings, Utc::now()).await?;
+        if retired > 0 {
+            eprintln!("Stopped polling {retired} users you no longer follow");
+        }
+    }
+    apply_interval_pins(&config.db_path, &config.interval_overrides, Utc::now()).await?;
+    fill_display_names(&config, &client).await?;
+
+    // A fresh database pulls every page for every following, so report progress.
+    let backfill = config.backfill || !has_any_stars(&config.db_path).await?;
+    let due = if config.backfill {
+        followed_users(&config.db_path).await?
+    } else {
+        due_users(&config.db_path, Utc::now()).await?
+    };
     if due.is_empty() {
-        return Ok(());
+        return Ok(PollOutcome::Complete);
+    }
+    let mut progress = backfill.then(|| BackfillProgress::new(due.len()));
+    if progress.is_some() {
+        eprintln!("Backfilling stars for {} users", due.len());
     }
 
-    let semaphore = Arc::new(Semaphore::new(config.max_concurrency));
+    // Fetches stay concurrent; their star writes are serialized and batched.
+    let (writer, writer_task) = StarWriter::spawn(&config.db_path, &config);
+    let total = due.len();
+    let mut started = 0;
+    let mut throttled = false;
+    let mut deferred_until = None;
+    let mut failure = None;
     let mut handles = futures::stream::FuturesUnordered::new();
     for user in due {
-        let permit = semaphore
-            .clone()
-            .acquire_owned()
-            .await
-            .expect("semaphore closed");
-        let client_clone = client.clone();
-        let config_clone = config.clone();
-        let db_path = config.db_path.clone();
+        let snapshot = client.rate_limit_snapshot();
+        match backpressure(
+            &snapshot,
+            config.rate_limit_pace_below,
+            config.rate_limit_reserve,
+            Utc::now(),
+        ) {
+            None => {}
+            Some(Backpressure::Reserve) => {
+                deferred_until = snapshot.reset_at;
+                break;
+            }
+            Some(Backpressure::Pace(delay)) => {
+                if !throttled {
+                    throttled = true;
+                    eprintln!(
+                        "GitHub rate limit low ({} requests left); starting one user every {}s until it resets",
+                        snapshot.remaining.unwrap_or_default(),
+                        delay.as_secs()
+                    );
+                }
+                if deadline.is_some_and(|deadline| tokio::time::Instant::now() + delay >= deadline)
+                {
+                    break;
+                }
+                if !wait_out_rate_limit(delay, shutdown.as_deref()).await {
+                    break;
+                }
+            }
+        }
+        if deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline) {
+            break;
+        }
+        // Users that finish while this one waits for a slot are settled right
+        // away, so progress is reported during the whole backfill.
+        let acquire = semaphore.clone().acquire_owned();
+        tokio::pin!(acquire);
+        let past_deadline = async {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(past_deadline);
+        let permit = loop {
+            tokio::select! {
+                permit = &mut acquire => break Some(permit.expect("semaphore closed")),
+                Some(result) = handles.next(), if !handles.is_empty() => {
+                    settle_user(result, progress.as_mut(), &mut failure);
+                }
+                _ = &mut past_deadline => break None,
+            }
+        };
+        let Some(permit) = permit else {
+            break;
+        };
+        started += 1;
+        let client = client.clone();
+        let config = Arc::clone(&config);
+        let writer = writer.clone();
         handles.push(tokio::spawn(async move {
-            let result = process_user(client_clone, &config_clone, &db_path, user).await;
+            let result =
+                process_user(client, &config, &config.db_path, &writer, user, deadline).await;
             drop(permit);
             result
         }));
     }
 
     while let Some(result) = handles.next().await {
-        match result {
-            Ok(Ok(())) => {}
-            Ok(Err(err)) => return Err(err),
-            Err(join_err) => return Err(join_err.into()),
+        settle_user(result, progress.as_mut(), &mut failure);
+    }
+    drop(writer);
+    writer_task.finish().await?;
+    if let Some(err) = failure {
+        return Err(err);
+    }
+
+    if started < total {
+        let skipped = total - started;
+        match deferred_until {
+            Some(reset_at) => eprintln!(
+                "GitHub rate limit down to the reserve of {}; {skipped} users deferred until it resets at {}",
+                config.rate_limit_reserve,
+                reset_at.to_rfc3339()
+            ),
+            None => eprintln!("Poll deadline reached; {skipped} users left for the next poll"),
         }
+        return Ok(PollOutcome::Partial { skipped });
     }
+    Ok(PollOutcome::Complete)
+}
 
+/// Looks up the profile names of a few users that have none yet. Names are
+/// cosmetic, so lookup failures end the batch without failing the poll.
+async fn fill_display_names(config: &Config, client: &GitHubClient) -> Result<()> {
+    if backpressure(
+        &client.rate_limit_snapshot(),
+        config.rate_limit_pace_below,
+        config.rate_limit_reserve,
+        Utc::now(),
+    )
+    .is_some()
+    {
+        return Ok(());
+    }
+    for (user_id, login) in users_missing_display_name(&config.db_path, DISPLAY_NAME_BATCH).await? {
+        let name = match client.fetch_display_name(&login).await {
+            Ok(name) => name,
+            Err(GitHubApiError::NotFound) => None,
+            Err(err) => {
+                eprintln!("Skipping display name lookups after {login} failed: {err}");
+                break;
+            }
+        };
+        record_display_name(&config.db_path, user_id, name, Utc::now()).await?;
+    }
     Ok(())
 }
 
-pub async fn build_feed_xml(config: &Config) -> Result<String> {
-    let events = recent_events_for_feed(&config.db_path, config.feed_length).await?;
-    let xml = feed::build_feed(&events, Utc::now())?;
+/// Counts a finished user towards the progress report and keeps the first
+/// failure, which ends the poll once the users in flight are done.
+fn settle_user(
+    result: Result<Result<()>, tokio::task::JoinError>,
+    progress: Option<&mut BackfillProgress>,
+    failure: &mut Option<anyhow::Error>,
+) {
+    // A finished user's savepoint is committed with the writer's next flush,
+    // so an interrupted backfill resumes from the users it had not reached.
+    let result = result
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    if let Some(progress) = progress {
+        progress.record_user(result.is_err());
+    }
+    if let Err(err) = result
+        && failure.is_none()
+    {
+        *failure = Some(err);
+    }
+}
+
+struct BackfillProgress {
+    total: usize,
+    done: usize,
+    failed: usize,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl BackfillProgress {
+    fn new(total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            total,
+            done: 0,
+            failed: 0,
+            started: now,
+            last_report: now,
+        }
+    }
+
+    fn record_user(&mut self, failed: bool) {
+        self.done += 1;
+        if failed {
+            self.failed += 1;
+        }
+        let finished = self.done == self.total;
+        if finished || failed || self.last_report.elapsed() >= BACKFILL_PROGRESS_INTERVAL {
+            self.last_report = Instant::now();
+            let failures = match self.failed {
+                0 => String::new(),
+                count => format!("{count} failed, "),
+            };
+            eprintln!(
+                "Backfill: processed {}/{} users ({failures}{}s elapsed)",
+                self.done,
+                self.total,
+                self.started.elapsed().as_secs()
+            );
+        }
+    }
+}
+
+/// Builds the main feed. `now` stamps `lastBuildDate` and anchors `--max-repo-idle-days`.
+pub async fn build_feed_xml(config: &Config, now: DateTime<Utc>) -> Result<String> {
+    let events = recent_events_for_feed(
+        &config.db_path,
+        config.feed_items(),
+        pushed_since(config, now),
+        config.max_items_per_user,
+    )
+    .await?;
+    let options = feed::FeedOptions {
+        enclosures: config.feed_enclosures,
+        order: config.feed_order,
+        title_suffix: None,
+        link: config.public_url_for("/"),
+        self_url: config.public_url_for("/feed.xml"),
+        hub_url: config
+            .serve_options()
+            .and_then(|opts| opts.websub_hub.clone()),
+    };
+    let xml = feed::build_feed(&events, now, &options)?;
     Ok(xml)
 }
 
+/// Size and freshness of the stars behind `/feed.xml`, for validators that
+/// must not render the feed itself.
+pub async fn feed_stats(config: &Config, now: DateTime<Utc>) -> Result<StarQueryStats> {
+    let query = StarQuery {
+        pushed_since: pushed_since(config, now),
+        max_per_user: config.max_items_per_user,
+        ..StarQuery::default()
+    };
+    star_query::query_stats(&config.db_path, &query).await
+}
+
+/// Builds the feed restricted to followings in one activity tier.
+pub async fn build_tier_feed_xml(
+    config: &Config,
+    tier: &str,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    let events = recent_events_by_tier(
+        &config.db_path,
+        tier,
+        config.feed_items(),
+        pushed_since(config, now),
+    )
+    .await?;
+    let options = feed::FeedOptions {
+        enclosures: config.feed_enclosures,
+        order: config.feed_order,
+        title_suffix: Some(format!("{tier} activity")),
+        link: config.public_url_for("/"),
+        self_url: config.public_url_for(&format!("/feed/tier/{tier}.xml")),
+        // Only `/feed.xml` is published to the WebSub hub.
+        hub_url: None,
+    };
+    let xml = feed::build_feed(&events, now, &options)?;
+    Ok(xml)
+}
+
+/// Builds the feed restricted to stars on repositories in one language, or
+/// `None` when no stored star matches it.
+pub async fn build_language_feed_xml(
+    config: &Config,
+    language: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<String>> {
+    let events = recent_events_by_language(
+        &config.db_path,
+        language,
+        config.feed_items(),
+        pushed_since(config, now),
+    )
+    .await?;
+    // Title the channel with the stored spelling rather than whatever was requested.
+    let Some(name) = events.first().and_then(|event| event.repo_language.clone()) else {
+        return Ok(None);
+    };
+    let encoded = utf8_percent_encode(language, NON_ALPHANUMERIC);
+    let options = feed::FeedOptions {
+        enclosures: config.feed_enclosures,
+        order: config.feed_order,
+        title_suffix: Some(name),
+        link: config.public_url_for("/"),
+        self_url: config.public_url_for(&format!("/feed/lang/{encoded}.xml")),
+        hub_url: None,
+    };
+    let xml = feed::build_feed(&events, now, &options)?;
+    Ok(Some(xml))
+}
+
+/// The rows behind a filtered feed: the caller's filters, capped at
+/// `feed_items()` newest stars and honouring `--max-repo-idle-days`.
+fn filtered_feed_query(config: &Config, filter: &StarQuery, now: DateTime<Utc>) -> StarQuery {
+    StarQuery {
+        pushed_since: pushed_since(config, now),
+        since_sequence: None,
+        page: 1,
+        page_size: config.feed_items(),
+        all: false,
+        ..filter.clone()
+    }
+}
+
+/// Size and freshness of the stars behind a filtered feed.
+pub async fn filtered_feed_stats(
+    config: &Config,
+    filter: &StarQuery,
+    now: DateTime<Utc>,
+) -> Result<StarQueryStats> {
+    let query = filtered_feed_query(config, filter, now);
+    star_query::query_stats(&config.db_path, &query).await
+}
+
+/// Builds a feed from any `/api/stars` filter, titled after the filter.
+pub async fn build_filtered_feed_xml(
+    config: &Config,
+    filter: &StarQuery,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    let query = filtered_feed_query(config, filter, now);
+    let result = star_query::query_stars(&config.db_path, &query).await?;
+    let options = feed::FeedOptions {
+        enclosures: config.feed_enclosures,
+        order: config.feed_order,
+        title_suffix: filter.describe_filters(),
+        link: config.public_url_for("/"),
+        self_url: config.public_url_for(&format!("/feed.xml?{}", filter.feed_query_string())),
+        hub_url: None,
+    };
+    let xml = feed::build_feed(&result.items, now, &options)?;
+    Ok(xml)
+}
+
+/// Cutoff for `--max-repo-idle-days`: repositories pushed before it are left out.
+fn pushed_since(config: &Config, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    config
+        .max_repo_idle_days
+        .map(|days| now - ChronoDuration::days(i64::from(days)))
+}
+
+/// A paginated list of accounts to track.
+#[derive(Debug, Clone)]
+enum Roster {
+    Followings,
+    OrgMembers(String),
+}
+
+impl Roster {
+    async fn fetch_page(
+        &self,
+        client: &GitHubClient,
+        page: usize,
+    ) -> Result<github::FollowingsPage, GitHubApiError> {
+        match self {
+            Roster::Followings => client.fetch_followings_page(page).await,
+            Roster::OrgMembers(org) => client.fetch_org_members_page(org, page).await,
+        }
+    }
+}
+
+impl std::fmt::Display for Roster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Roster::Followings => f.write_str("followings"),
+            Roster::OrgMembers(org) => write!(f, "members of org {org}"),
+        }
+    }
+}
+
+/// Fetches every followed user. The first page reveals the page count through
+/// the `Link` header so the remaining pages are requested in parallel, each
+/// holding a permit from `semaphore`; without that header pages are walked
+/// sequentially.
 pub async fn fetch_followings_with_retry(
     client: Arc<GitHubClient>,
+    semaphore: Arc<Semaphore>,
+    shutdown: Option<Arc<Notify>>,
 ) -> Result<Vec<github::FollowingUser>> {
+    fetch_roster_with_retry(client, semaphore, shutdown, Roster::Followings).await
+}
+
+/// Fetches every member of `org` visible to the token, paginated like
+/// [`fetch_followings_with_retry`]. Returns `None` when the org doesn't exist.
+pub async fn fetch_org_members_with_retry(
+    client: Arc<GitHubClient>,
+    semaphore: Arc<Semaphore>,
+    shutdown: Option<Arc<Notify>>,
+    org: &str,
+) -> Result<Option<Vec<github::FollowingUser>>> {
+    let roster = Roster::OrgMembers(org.to_string());
+    match fetch_roster_with_retry(client, semaphore, shutdown, roster).await {
+        Ok(members) => Ok(Some(members)),
+        Err(err) if matches!(err.downcast_ref(), Some(GitHubApiError::NotFound)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+async fn fetch_roster_with_retry(
+    client: Arc<GitHubClient>,
+    semaphore: Arc<Semaphore>,
+    shutdown: Option<Arc<Notify>>,
+    roster: Roster,
+) -> Result<Vec<github::FollowingUser>> {
+    let first =
+        fetch_roster_page_with_retry(&client, &semaphore, shutdown.as_deref(), &roster, 1).await?;
+    let mut users = Vec::new();
+
+    match first.last_page {
+        Some(last_page) => {
+            users.extend(first.users);
+            let pages = (2..=last_page)
+                .map(|page| {
+                    let client = client.clone();
+                    let semaphore = semaphore.clone();
+                    let shutdown = shutdown.clone();
+                    let roster = roster.clone();
+                    tokio::spawn(async move {
+                        fetch_roster_page_with_retry(
+                            &client,
+                            &semaphore,
+                            shutdown.as_deref(),
+                            &roster,
+                            page,
+                        )
+                        .await
+                    })
+                })
+                .collect::<Vec<_>>();
+            // Join in page order so the result matches GitHub's ordering.
+            for handle in pages {
+                users.extend(handle.await??.users);
+            }
+        }
+        None => {
+            let mut page = 1;
+            let mut current = first;
+            loop {
+                let has_more = current.is_full();
+                users.extend(current.users);
+                if !has_more {
+                    break;
+                }
+                page += 1;
+                current = fetch_roster_page_with_retry(
+                    &client,
+                    &semaphore,
+                    shutdown.as_deref(),
+                    &roster,
+                    page,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(users)
+}
+
+async fn fetch_roster_page_with_retry(
+    client: &GitHubClient,
+    semaphore: &Semaphore,
+    shutdown: Option<&Notify>,
+    roster: &Roster,
+    page: usize,
+) -> Result<github::FollowingsPage> {
     loop {
-        match client.fetch_followings().await {
-            Ok(users) => return Ok(users),
+        let permit = semaphore.acquire().await.expect("semaphore closed");
+        let result = roster.fetch_page(client, page).await;
+        drop(permit);
+        match result {
+            Ok(batch) => return Ok(batch),
             Err(GitHubApiError::RateLimited(wait)) => {
+                if wait > MAX_FOLLOWINGS_RATE_LIMIT_WAIT {
+                    return Err(anyhow!(
+                        "rate limited while fetching {roster} for {} seconds (more than the {} second cap); skipping this poll",
+                        wait.as_secs(),
+                        MAX_FOLLOWINGS_RATE_LIMIT_WAIT.as_secs()
+                    ));
+                }
                 eprintln!(
-                    "Rate limited while fetching followings, sleeping {} seconds",
+                    "Rate limited while fetching {roster}, sleeping {} seconds",
                     wait.as_secs()
                 );
-                tokio::time::sleep(wait).await;
+                if !wait_out_rate_limit(wait, shutdown).await {
+                    return Err(anyhow!(
+                        "shutdown requested while waiting out the {roster} rate limit"
+                    ));
+                }
             }
             Err(GitHubApiError::Auth) => {
                 return Err(anyhow!("GitHub authentication failed. Check your token."));
@@ -76,34 +620,129 @@ pub async fn fetch_followings_with_retry(
             Err(GitHubApiError::Forbidden) => {
                 return Err(anyhow!("GitHub API access forbidden."));
             }
+            // Let org lookups tell a missing org apart from other failures.
+            Err(GitHubApiError::NotFound) if matches!(roster, Roster::OrgMembers(_)) => {
+                return Err(GitHubApiError::NotFound.into());
+            }
+            Err(err @ (GitHubApiError::Unexpected { .. } | GitHubApiError::NotFound)) => {
+                return Err(anyhow!("failed to fetch {roster}: {err}"));
+            }
             Err(GitHubApiError::Other(err)) => return Err(err),
         }
     }
 }
 
+/// How the poller holds back as the rate-limit window runs down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backpressure {
+    /// Wait this long before starting the next user.
+    Pace(Duration),
+    /// At or below the reserve: start no more users until the window resets.
+    Reserve,
+}
+
+/// Once fewer than `pace_below` requests remain above `reserve`, user fetches
+/// are spaced so those requests last until the reset; at a non-zero reserve
+/// they stop. A snapshot whose reset has passed no longer holds anything back.
+fn backpressure(
+    snapshot: &RateLimitSnapshot,
+    pace_below: u32,
+    reserve: u32,
+    now: DateTime<Utc>,
+) -> Option<Backpressure> {
+    let remaining = snapshot.remaining?;
+    let until_reset = (snapshot.reset_at? - now).to_std().ok()?;
+    let headroom = remaining.saturating_sub(reserve);
+    if reserve > 0 && headroom == 0 {
+        return Some(Backpressure::Reserve);
+    }
+    (headroom < pace_below).then(|| Backpressure::Pace(until_reset / headroom.max(1)))
+}
+
+/// Sleeps for `wait`, logging the remaining time periodically. Returns `false`
+/// if `shutdown` fired before the wait elapsed.
+async fn wait_out_rate_limit(wait: Duration, shutdown: Option<&Notify>) -> bool {
+    let deadline = tokio::time::Instant::now() + wait;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        let chunk = remaining.min(RATE_LIMIT_LOG_INTERVAL);
+        match shutdown {
+            Some(notify) => {
+                tokio::select! {
+                    _ = notify.notified() => return false,
+                    _ = tokio::time::sleep(chunk) => {}
+                }
+            }
+            None => tokio::time::sleep(chunk).await,
+        }
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if !remaining.is_zero() {
+            eprintln!("Still rate limited, {}s remaining", remaining.as_secs());
+        }
+    }
+}
+
 pub async fn process_user(
     client: Arc<GitHubClient>,
     config: &Config,
     db_path: &std::path::Path,
+    writer: &StarWriter,
     user: UserRecord,
+    deadline: Option<tokio::time::Instant>,
 ) -> Result<()> {
-    let known_latest = user.last_starred_at;
-    let outcome = client
-        .fetch_starred(
-            &user.login,
-            user.etag.as_deref(),
-            user.last_modified.as_deref(),
-            known_latest,
-        )
-        .await;
+    // Without stored history, an optional cutoff keeps the first fetch from walking years of stars.
+    let cutoff = config
+        .backfill_since_days
+        .map(|days| Utc::now() - ChronoDuration::days(i64::from(days)));
+    let known_latest = user.last_starred_at.or(cutoff);
+    let resume_page = user
+        .backfill_page
+        .and_then(|page| usize::try_from(page).ok())
+        .filter(|&page| page > 0);
+    let first_page = match resume_page {
+        Some(page) => Some((page, cutoff)),
+        None if config.backfill_ascending && user.last_starred_at.is_none() => {
+            Some((1, known_latest))
+        }
+        None => None,
+    };
+    let outcome = if let Some((first_page, since)) = first_page {
+        // Pages are committed as they arrive, so only failures are left to handle.
+        let walk = AscendingWalk {
+            first_page,
+            since,
+            max_pages: config.max_pages_per_user,
+            deadline,
+        };
+        match backfill_ascending(&client, db_path, writer, &user, walk).await {
+            Ok(()) => return Ok(()),
+            Err(err) => Err(err),
+        }
+    } else {
+        client
+            .fetch_starred(
+                &user.login,
+                user.etag.as_deref(),
+                user.last_modified.as_deref(),
+                known_latest,
+            )
+            .await
+    };
 
     match outcome {
-        Ok(StarFetchOutcome::NotModified { fetched_at }) => {
+        Ok(StarFetchOutcome::NotModified {
+            fetched_at,
+            poll_interval,
+        }) => {
             record_not_modified(
                 db_path,
                 user.user_id,
                 fetched_at,
                 user.fetch_interval_minutes,
+                poll_interval,
             )
             .await?;
         }
@@ -112,23 +751,35 @@ pub async fn process_user(
             etag,
             last_modified,
             events,
+            poll_interval,
+            truncated,
         }) => {
-            let new_interval = insert_star_events(
-                db_path,
-                &user,
-                &events,
-                fetched_at,
-                etag,
-                last_modified,
-                config,
-            )
-            .await?;
-            println!(
-                "{} new events for {} (next fetch in {} minutes)",
-                events.len(),
-                user.login,
-                new_interval
-            );
+            let login = user.login.clone();
+            let user_id = user.user_id;
+            // Stars already stored are the oldest ones, so the walk picks up
+            // around where they end; a page of overlap is ignored on insert.
+            let resume_page = (user.star_count.max(0) as usize / github::PER_PAGE).max(1);
+            let event_count = events.len();
+            let new_interval = writer
+                .submit(StarBatch {
+                    user,
+                    events,
+                    fetched_at,
+                    etag,
+                    last_modified,
+                    poll_interval,
+                })
+                .await?;
+            if truncated {
+                record_backfill_page(db_path, user_id, Some(resume_page), Utc::now()).await?;
+                println!(
+                    "{event_count} new events for {login}; older stars continue from page {resume_page} next poll"
+                );
+            } else {
+                println!(
+                    "{event_count} new events for {login} (next fetch in {new_interval} minutes)"
+                );
+            }
         }
         Err(GitHubApiError::RateLimited(wait)) => {
             eprintln!(
@@ -137,7 +788,10 @@ pub async fn process_user(
                 wait.as_secs()
             );
             defer_user(db_path, user.user_id, wait).await?;
-            tokio::time::sleep(wait).await;
+            // The deferral is already stored, so the poll deadline may cut the pause short.
+            let resume = tokio::time::Instant::now() + wait;
+            tokio::time::sleep_until(deadline.map_or(resume, |deadline| resume.min(deadline)))
+                .await;
         }
         Err(GitHubApiError::Auth) => {
             return Err(anyhow!(
@@ -151,7 +805,173 @@ pub async fn process_user(
                 user.login
             ));
         }
+        Err(GitHubApiError::NotFound) => {
+            let failures = record_user_not_found(
+                db_path,
+                user.user_id,
+                config.max_interval_minutes,
+                config.activity_tiers.slowest(),
+            )
+            .await?;
+            eprintln!(
+                "User {} not found on GitHub ({} consecutive); deferring for {} minutes",
+                user.login, failures, config.max_interval_minutes
+            );
+        }
+        Err(err @ GitHubApiError::Unexpected { .. }) => {
+            return Err(anyhow!("failed to fetch stars for {}: {err}", user.login));
+        }
         Err(GitHubApiError::Other(err)) => return Err(err),
     }
     Ok(())
 }
+
+/// Where an oldest-first walk starts and what may stop it early.
+struct AscendingWalk {
+    first_page: usize,
+    /// Stars before this are dropped, though their pages still have to be read.
+    since: Option<DateTime<Utc>>,
+    max_pages: Option<usize>,
+    deadline: Option<tokio::time::Instant>,
+}
+
+/// Walks a user's stars oldest-first, committing each page before asking for
+/// the next. An interrupted walk leaves a contiguous run of the earliest stars,
+/// which the next newest-first poll completes from `last_starred_at`. A walk
+/// stopped by `--max-pages-per-user` instead records its next page, and the
+/// following poll resumes there.
+async fn backfill_ascending(
+    client: &GitHubClient,
+    db_path: &std::path::Path,
+    writer: &StarWriter,
+    user: &UserRecord,
+    walk: AscendingWalk,
+) -> Result<(), GitHubApiError> {
+    let mut user = user.clone();
+    let mut page = walk.first_page;
+    let mut stored = 0;
+    loop {
+        let starred = client
+            .fetch_starred_page_ascending(&user.login, page)
+            .await?;
+        let has_more = starred.is_full();
+        let events: Vec<_> = starred
+            .events
+            .into_iter()
+            .filter(|event| walk.since.is_none_or(|since| event.starred_at > since))
+            .collect();
+        stored += events.len();
+        let new_interval = writer
+            .submit(StarBatch {
+                user: user.clone(),
+                events,
+                fetched_at: Utc::now(),
+                etag: None,
+                last_modified: None,
+                poll_interval: starred.poll_interval,
+            })
+            .await?;
+        let past_deadline = walk
+            .deadline
+            .is_some_and(|deadline| tokio::time::Instant::now() >= deadline);
+        let capped = walk
+            .max_pages
+            .is_some_and(|max| page + 1 - walk.first_page >= max);
+        // A resumed walk keeps its place; a first walk is finished newest-first.
+        let keep_place = past_deadline && user.backfill_page.is_some();
+        if has_more && (capped || keep_place) {
+            record_backfill_page(db_path, user.user_id, Some(page + 1), Utc::now()).await?;
+            println!(
+                "{stored} new events for {} oldest-first; continuing from page {} next poll",
+                user.login,
+                page + 1
+            );
+            return Ok(());
+        }
+        if !has_more && user.backfill_page.is_some() {
+            record_backfill_page(db_path, user.user_id, None, Utc::now()).await?;
+        }
+        if !has_more || past_deadline {
+            println!(
+                "{stored} new events for {} oldest-first (next fetch in {new_interval} minutes)",
+                user.login
+            );
+            return Ok(());
+        }
+        // The next page's gaps and counts build on what this one stored.
+        user = user_by_login(db_path, &user.login)
+            .await?
+            .ok_or_else(|| anyhow!("user {} disappeared during backfill", user.login))?;
+        page += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backpressure_spreads_the_budget_above_the_reserve_until_reset() {
+        let now = Utc::now();
+        let snapshot = |remaining, reset_in_secs| RateLimitSnapshot {
+            remaining: Some(remaining),
+            reset_at: Some(now + ChronoDuration::seconds(reset_in_secs)),
+            ..RateLimitSnapshot::default()
+        };
+        // The defaults pace below 500 and keep no reserve.
+        assert_eq!(backpressure(&snapshot(4000, 600), 500, 0, now), None);
+        assert_eq!(
+            backpressure(&snapshot(100, 600), 500, 0, now),
+            Some(Backpressure::Pace(Duration::from_secs(6)))
+        );
+        assert_eq!(
+            backpressure(&snapshot(0, 60), 500, 0, now),
+            Some(Backpressure::Pace(Duration::from_secs(60)))
+        );
+        // With a reserve, pacing spreads what is left above it.
+        assert_eq!(backpressure(&snapshot(1000, 600), 500, 500, now), None);
+        assert_eq!(
+            backpressure(&snapshot(600, 600), 500, 500, now),
+            Some(Backpressure::Pace(Duration::from_secs(6)))
+        );
+        assert_eq!(
+            backpressure(&snapshot(500, 60), 500, 500, now),
+            Some(Backpressure::Reserve)
+        );
+        assert_eq!(
+            backpressure(&snapshot(40, 60), 0, 50, now),
+            Some(Backpressure::Reserve)
+        );
+        // Disabled, unknown, or already reset.
+        assert_eq!(backpressure(&snapshot(10, 600), 0, 0, now), None);
+        assert_eq!(
+            backpressure(&RateLimitSnapshot::default(), 500, 500, now),
+            None
+        );
+        assert_eq!(backpressure(&snapshot(10, -5), 500, 500, now), None);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_wait_stops_on_shutdown() {
+        let notify = Notify::new();
+        notify.notify_one();
+        let completed = wait_out_rate_limit(Duration::from_secs(3600), Some(&notify)).await;
+        assert!(!completed);
+        assert!(wait_out_rate_limit(Duration::from_millis(5), None).await);
+    }
+
+    #[test]
+    fn failed_users_count_towards_backfill_progress() {
+        let mut progress = BackfillProgress::new(3);
+        let mut failure = None;
+        settle_user(Ok(Ok(())), Some(&mut progress), &mut failure);
+        settle_user(Ok(Err(anyhow!("first"))), Some(&mut progress), &mut failure);
+        settle_user(
+            Ok(Err(anyhow!("second"))),
+            Some(&mut progress),
+            &mut failure,
+        );
+        assert_eq!((progress.done, progress.failed), (3, 2));
+        assert_eq!(failure.unwrap().to_string(), "first");
+    }
+}
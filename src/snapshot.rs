@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+const SNAPSHOT_PREFIX: &str = "feed-";
+const SNAPSHOT_SUFFIX: &str = ".xml";
+
+/// Writes `xml` to `dir/feed-{timestamp}.xml` and deletes the oldest snapshots
+/// so at most `keep` remain. Returns the path that was written.
+pub fn write_feed_snapshot(
+    dir: &Path,
+    xml: &str,
+    generated_at: DateTime<Utc>,
+    keep: usize,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create snapshot dir {}", dir.display()))?;
+    // Fixed-width UTC timestamps sort lexically in chronological order.
+    let name = format!(
+        "{SNAPSHOT_PREFIX}{}{SNAPSHOT_SUFFIX}",
+        generated_at.format("%Y%m%dT%H%M%S%.3fZ")
+    );
+    let path = dir.join(name);
+    fs::write(&path, xml)
+        .with_context(|| format!("failed to write feed snapshot {}", path.display()))?;
+    prune_snapshots(dir, keep)?;
+    Ok(path)
+}
+
+fn prune_snapshots(dir: &Path, keep: usize) -> Result<()> {
+    let mut snapshots = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(SNAPSHOT_SUFFIX)
+                })
+        })
+        .collect::<Vec<_>>();
+    if snapshots.len() <= keep {
+        return Ok(());
+    }
+    snapshots.sort();
+    let excess = snapshots.len() - keep;
+    for path in snapshots.into_iter().take(excess) {
+        fs::remove_file(&path)
+            .with_context(|| format!("failed to remove old snapshot {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn keeps_only_newest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+        let start = Utc::now();
+        for offset in 0..5 {
+            write_feed_snapshot(
+                dir.path(),
+                &format!("<rss>{offset}</rss>"),
+                start + Duration::seconds(offset),
+                3,
+            )
+            .unwrap();
+        }
+
+        let mut names = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names.len(), 4);
+        assert!(names.contains(&"notes.txt".to_string()));
+        let newest = dir.path().join(&names[2]);
+        assert_eq!(fs::read_to_string(newest).unwrap(), "<rss>4</rss>");
+    }
+}
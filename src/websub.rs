@@ -0,0 +1,52 @@
+//! WebSub (PubSubHubbub) publishing: tells a hub that `/feed.xml` changed so
+//! it can push the new items to subscribers instead of waiting for them to poll.
+
+use anyhow::{Context, Result, anyhow};
+use reqwest::{Client, header};
+
+use crate::config::Config;
+use crate::github::{http_client_builder, user_agent};
+
+pub struct HubPublisher {
+    client: Client,
+    hub: String,
+    topic: String,
+}
+
+impl HubPublisher {
+    /// A publisher announcing `topic` (the feed's public URL) to `hub`. Uses
+    /// the configured User-Agent, timeouts, and proxy.
+    pub fn new(config: &Config, hub: &str, topic: &str) -> Result<Self> {
+        let client = http_client_builder(config)?
+            .user_agent(user_agent(&config.user_agent, config.contact.as_deref()))
+            .build()
+            .context("failed to build reqwest client")?;
+        Ok(Self {
+            client,
+            hub: hub.to_string(),
+            topic: topic.to_string(),
+        })
+    }
+
+    /// Sends `hub.mode=publish`. Hubs answer `2xx` once they have queued a fetch.
+    pub async fn publish(&self) -> Result<()> {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("hub.mode", "publish")
+            .append_pair("hub.url", &self.topic)
+            .finish();
+        let response = self
+            .client
+            .post(&self.hub)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("failed to reach WebSub hub {}", self.hub))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("WebSub hub {} answered {status}: {body}", self.hub));
+        }
+        Ok(())
+    }
+}
@@ -1,56 +1,1191 @@
-use std::path::PathBuf;
+use std::path::Path;
 
 use chrono::{TimeZone, Utc};
 use httpmock::prelude::*;
 use rusqlite::Connection;
 use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 use warp::http::StatusCode;
 
+use hoshiyomi::clock::FixedClock;
 use hoshiyomi::config::{Config, Mode};
 use hoshiyomi::db::{self, StarFeedRow};
-use hoshiyomi::feed;
-use hoshiyomi::github::{GitHubApiError, GitHubClient};
+use hoshiyomi::feed::{self, FeedOptions};
+use hoshiyomi::github::{GitHubApiError, GitHubClient, StarFetchOutcome};
+use hoshiyomi::pipeline::{PollOutcome, fetch_followings_with_retry, poll_once};
 use hoshiyomi::server::{self, AppState, SchedulerState};
+use hoshiyomi::websub::HubPublisher;
+use tokio::sync::Semaphore;
+
+fn test_config(db_path: &Path, api_base: &str) -> Config {
+    Config {
+        github_token: "test-token".into(),
+        db_path: db_path.to_path_buf(),
+        max_concurrency: 1,
+        rate_limit_pace_below: 0,
+        rate_limit_reserve: 0,
+        feed_length: 10,
+        html_length: 10,
+        default_interval_minutes: 60,
+        min_interval_minutes: 10,
+        max_interval_minutes: 7 * 24 * 60,
+        ema_alpha: 0.3,
+        activity_tiers: Default::default(),
+        api_base_url: Url::parse(api_base).unwrap(),
+        user_agent: "following-stars-rss-test".into(),
+        contact: None,
+        timeout_secs: 5,
+        connect_timeout_secs: 10,
+        max_response_bytes: 32 * 1024 * 1024,
+        no_conditional: false,
+        proxy: Default::default(),
+        repo_filter: Default::default(),
+        include_self: false,
+        keep_unfollowed: false,
+        extra_users: Vec::new(),
+        extra_orgs: Vec::new(),
+        interval_overrides: Vec::new(),
+        feed_enclosures: false,
+        feed_order: Default::default(),
+        max_repo_idle_days: None,
+        max_items_per_user: None,
+        feed_warn_threshold: 100,
+        feed_hard_max: 1000,
+        backfill: false,
+        backfill_ascending: false,
+        backfill_since_days: None,
+        poll_deadline_secs: None,
+        max_pages_per_user: None,
+        sqlite: Default::default(),
+        mode: Mode::Once,
+    }
+}
+
+#[tokio::test]
+async fn github_client_returns_rate_limited_error() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("per_page", "100")
+                .query_param("page", "1");
+            then.status(403)
+                .header("Retry-After", "60")
+                .header("x-github-request-id", "ABCD:5678")
+                .header("x-ratelimit-remaining", "0")
+                .header("x-ratelimit-used", "30")
+                .header("x-ratelimit-resource", "search");
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+
+    let client = GitHubClient::new(&config).unwrap();
+    let err = client
+        .fetch_starred("alice", None, None, None)
+        .await
+        .expect_err("expected rate limit error");
+
+    match err {
+        GitHubApiError::RateLimited(wait) => assert_eq!(wait.as_secs(), 60),
+        other => panic!("expected rate limited error, got {other:?}"),
+    }
+    let snapshot = client.rate_limit_snapshot();
+    assert_eq!(snapshot.remaining, Some(0));
+    assert_eq!(snapshot.used, Some(30));
+    assert_eq!(snapshot.resource.as_deref(), Some("search"));
+}
+
+#[tokio::test]
+async fn github_client_surfaces_unexpected_status() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(422)
+                .header("x-github-request-id", "ABCD:1234")
+                .body("{\"message\":\"Validation Failed\"}");
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+
+    let client = GitHubClient::new(&config).unwrap();
+    let err = client
+        .fetch_starred("alice", None, None, None)
+        .await
+        .expect_err("expected unexpected status error");
+
+    match err {
+        GitHubApiError::Unexpected { status, body } => {
+            assert_eq!(status.as_u16(), 422);
+            assert!(body.contains("Validation Failed"));
+        }
+        other => panic!("expected unexpected status error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn unreachable_github_is_retried_until_it_answers() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Nothing listens on the port at first, so the first attempts are refused.
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let github = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]")
+            .await
+            .unwrap();
+    });
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &format!("http://{addr}/"));
+
+    let client = GitHubClient::new(&config)
+        .unwrap()
+        .with_connect_retry_delay(Duration::from_millis(100));
+    let page = client.fetch_followings_page(1).await.unwrap();
+    assert!(page.users.is_empty());
+    github.await.unwrap();
+}
+
+#[tokio::test]
+async fn github_client_requests_newest_first_and_filters_out_of_order_pages() {
+    let server = MockServer::start_async().await;
+
+    let star = |repo: &str, starred_at: &str| {
+        format!(
+            "{{\"starred_at\":\"{starred_at}\",\"repo\":{{\"full_name\":\"{repo}\",\"description\":null,\"html_url\":\"https://github.com/{repo}\",\"language\":null}}}}"
+        )
+    };
+    let body = format!(
+        "[{},{},{}]",
+        star("acme/newest", "2025-01-03T00:00:00Z"),
+        star("acme/old", "2024-12-01T00:00:00Z"),
+        star("acme/newer", "2025-01-02T00:00:00Z"),
+    );
+    let sorted = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("sort", "created")
+                .query_param("direction", "desc")
+                .query_param("page", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(body);
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+
+    let client = GitHubClient::new(&config).unwrap();
+    let known_latest = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let outcome = client
+        .fetch_starred("alice", None, None, Some(known_latest))
+        .await
+        .expect("fetch");
+
+    match outcome {
+        StarFetchOutcome::Modified { events, .. } => {
+            let repos: Vec<_> = events.iter().map(|e| e.repo_full_name.as_str()).collect();
+            assert_eq!(repos, vec!["acme/newest", "acme/newer"]);
+        }
+        other => panic!("expected new events, got {other:?}"),
+    }
+    sorted.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn github_client_skips_validators_when_conditional_disabled() {
+    let server = MockServer::start_async().await;
+
+    let conditional = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .header_exists("if-none-match");
+            then.status(304);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(200)
+                .header("ETag", "\"fresh\"")
+                .header("content-type", "application/json")
+                .body("[]");
+        })
+        .await;
+
+    let config = Config {
+        no_conditional: true,
+        ..test_config(Path::new("/tmp/ignored.db"), &server.base_url())
+    };
+
+    let client = GitHubClient::new(&config).unwrap();
+    let outcome = client
+        .fetch_starred(
+            "alice",
+            Some("\"stale\""),
+            Some("Sat, 18 Oct 2025 04:15:00 GMT"),
+            None,
+        )
+        .await
+        .expect("fetch");
+
+    match outcome {
+        StarFetchOutcome::Modified {
+            etag,
+            last_modified,
+            ..
+        } => {
+            assert!(etag.is_none());
+            assert!(last_modified.is_none());
+        }
+        other => panic!("expected a full fetch, got {other:?}"),
+    }
+    conditional.assert_hits_async(0).await;
+}
+
+#[tokio::test]
+async fn starred_fetch_reports_github_poll_interval() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(304).header("X-Poll-Interval", "3600");
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+
+    let client = GitHubClient::new(&config).unwrap();
+    let outcome = client
+        .fetch_starred("alice", Some("\"cached\""), None, None)
+        .await
+        .expect("fetch");
+
+    match outcome {
+        StarFetchOutcome::NotModified { poll_interval, .. } => {
+            assert_eq!(poll_interval, Some(Duration::from_secs(3600)));
+        }
+        other => panic!("expected not modified, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn resolve_logins_returns_ids_and_skips_unknown_accounts() {
+    let server = MockServer::start_async().await;
+    for (login, id) in [("alice", 1), ("bob", 2)] {
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/users/{login}"));
+                then.status(200)
+                    .body(format!("{{\"login\":\"{login}\",\"id\":{id}}}"));
+            })
+            .await;
+    }
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/ghost");
+            then.status(404).body("{\"message\":\"Not Found\"}");
+        })
+        .await;
+
+    let config = Config {
+        max_concurrency: 2,
+        ..test_config(Path::new("/tmp/ignored.db"), &server.base_url())
+    };
+
+    let client = GitHubClient::new(&config).unwrap();
+    let logins = ["bob", "ghost", "alice"].map(String::from);
+    let users = client.resolve_logins(&logins).await.expect("resolve");
+    let resolved: Vec<_> = users.iter().map(|u| (u.login.as_str(), u.id)).collect();
+    assert_eq!(resolved, vec![("bob", 2), ("alice", 1)]);
+}
+
+#[tokio::test]
+async fn resolve_logins_names_the_login_that_failed() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice");
+            then.status(200).body("{\"login\":\"alice\",\"id\":1}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/broken");
+            then.status(500).body("{\"message\":\"Server Error\"}");
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+    let client = GitHubClient::new(&config).unwrap();
+    let logins = ["alice", "broken"].map(String::from);
+    let err = client.resolve_logins(&logins).await.unwrap_err();
+    assert!(format!("{err:#}").starts_with("broken: "), "{err:#}");
+}
+
+#[tokio::test]
+async fn followings_pages_are_fetched_using_link_header() {
+    let server = MockServer::start_async().await;
+    let last_link = format!(
+        "<{}/user/following?per_page=100&page=3>; rel=\"last\"",
+        server.base_url()
+    );
+    let mut page_mocks = Vec::new();
+    for page in 1..=3 {
+        let link = last_link.clone();
+        let body = format!("[{{\"login\":\"user{page}\",\"id\":{page}}}]");
+        page_mocks.push(
+            server
+                .mock_async(move |when, then| {
+                    when.method(GET)
+                        .path("/user/following")
+                        .query_param("page", page.to_string());
+                    then.status(200).header("Link", link).body(body);
+                })
+                .await,
+        );
+    }
+
+    let config = Config {
+        max_concurrency: 2,
+        ..test_config(Path::new("/tmp/ignored.db"), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let users = fetch_followings_with_retry(client, Arc::new(Semaphore::new(2)), None)
+        .await
+        .unwrap();
+    let logins: Vec<_> = users.iter().map(|user| user.login.as_str()).collect();
+    assert_eq!(logins, vec!["user1", "user2", "user3"]);
+    for mock in &page_mocks {
+        mock.assert_hits_async(1).await;
+    }
+}
+
+#[tokio::test]
+async fn oversized_responses_are_rejected() {
+    let server = MockServer::start_async().await;
+    let users = (1..=20)
+        .map(|id| format!("{{\"login\":\"user{id}\",\"id\":{id}}}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    server
+        .mock_async(move |when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body(format!("[{users}]"));
+        })
+        .await;
+    let stars = format!(
+        "[{{\"starred_at\":\"2025-10-18T01:00:00Z\",\"repo\":{{\"full_name\":\"acme/widget\",\"html_url\":\"https://github.com/acme/widget\",\"description\":\"{}\"}}}}]",
+        "x".repeat(512)
+    );
+    server
+        .mock_async(move |when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(200).body(stars);
+        })
+        .await;
+
+    let config = Config {
+        max_response_bytes: 256,
+        ..test_config(Path::new("/tmp/ignored.db"), &server.base_url())
+    };
+
+    let client = GitHubClient::new(&config).unwrap();
+    let err = client
+        .fetch_followings()
+        .await
+        .expect_err("followings over the limit");
+    assert!(
+        err.to_string().contains("larger than --max-response-bytes"),
+        "{err}"
+    );
+    let err = client
+        .fetch_starred("alice", None, None, None)
+        .await
+        .expect_err("starred page over the limit");
+    assert!(
+        err.to_string().contains("larger than --max-response-bytes"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn include_self_tracks_authenticated_users_stars() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let me = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user");
+            then.status(200).body("{\"login\":\"me\",\"id\":1}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/bob/starred");
+            then.status(200).body("[]");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/me/starred");
+            then.status(200).body(
+                "[{\"starred_at\":\"2025-01-03T00:00:00Z\",\"repo\":{\"full_name\":\"acme/mine\",\"description\":null,\"html_url\":\"https://github.com/acme/mine\",\"language\":null}}]",
+            );
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        include_self: true,
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let config = Arc::new(config);
+    poll_once(Arc::clone(&config), client.clone(), None)
+        .await
+        .unwrap();
+    let events = db::recent_events_for_feed(temp.path(), 10, None, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].login, "me");
+    assert_eq!(events[0].repo_full_name, "acme/mine");
+
+    // The login is resolved once per client.
+    poll_once(config, client, None).await.unwrap();
+    me.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn display_names_are_looked_up_once_per_user() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .body("[{\"login\":\"bob\",\"id\":2},{\"login\":\"carol\",\"id\":3}]");
+        })
+        .await;
+    let bob = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/bob");
+            then.status(200)
+                .body("{\"login\":\"bob\",\"id\":2,\"name\":\"Bob Builder\"}");
+        })
+        .await;
+    let carol = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/carol");
+            then.status(200)
+                .body("{\"login\":\"carol\",\"id\":3,\"name\":null}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/bob/starred");
+            then.status(200).body(
+                "[{\"starred_at\":\"2025-01-03T00:00:00Z\",\"repo\":{\"full_name\":\"acme/bob\",\"description\":null,\"html_url\":\"https://github.com/acme/bob\",\"language\":null}}]",
+            );
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/carol/starred");
+            then.status(200).body(
+                "[{\"starred_at\":\"2025-01-02T00:00:00Z\",\"repo\":{\"full_name\":\"acme/carol\",\"description\":null,\"html_url\":\"https://github.com/acme/carol\",\"language\":null}}]",
+            );
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Arc::new(Config {
+        max_concurrency: 2,
+        ..test_config(temp.path(), &server.base_url())
+    });
+
+    // A fresh client each time, so only the stored lookups prevent repeats.
+    for _ in 0..2 {
+        let client = Arc::new(GitHubClient::new(&config).unwrap());
+        poll_once(Arc::clone(&config), client, None).await.unwrap();
+    }
+    bob.assert_hits_async(1).await;
+    carol.assert_hits_async(1).await;
+
+    let options = db::star_query::options_snapshot(temp.path(), None)
+        .await
+        .unwrap();
+    let names = options
+        .users
+        .iter()
+        .map(|user| (user.login.as_str(), user.display_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, [("bob", "Bob Builder"), ("carol", "carol")]);
+}
+
+#[tokio::test]
+async fn tracked_users_are_polled_without_following_them() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[]");
+        })
+        .await;
+    let lookup = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/carol");
+            then.status(200).body("{\"login\":\"carol\",\"id\":3}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/ghost");
+            then.status(404).body("{\"message\":\"Not Found\"}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/carol/starred");
+            then.status(200).body(
+                "[{\"starred_at\":\"2025-01-04T00:00:00Z\",\"repo\":{\"full_name\":\"acme/tracked\",\"description\":null,\"html_url\":\"https://github.com/acme/tracked\",\"language\":null}}]",
+            );
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        extra_users: vec!["carol".into(), "ghost".into()],
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let config = Arc::new(config);
+    poll_once(Arc::clone(&config), client.clone(), None)
+        .await
+        .unwrap();
+    let events = db::recent_events_for_feed(temp.path(), 10, None, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].login, "carol");
+    assert_eq!(events[0].repo_full_name, "acme/tracked");
+
+    poll_once(config, client, None).await.unwrap();
+    lookup.assert_hits_async(1).await;
+}
+
+#[tokio::test]
+async fn unfollowed_users_stop_being_polled() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"alice\",\"id\":1}]");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/orgs/ghost/members");
+            then.status(404).body("{\"message\":\"Not Found\"}");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(200).body("[]");
+        })
+        .await;
+    let bob_starred = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/bob/starred");
+            then.status(200).body("[]");
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let previously_followed = [
+        hoshiyomi::github::FollowingUser {
+            id: 1,
+            login: "alice".into(),
+        },
+        hoshiyomi::github::FollowingUser {
+            id: 2,
+            login: "bob".into(),
+        },
+    ];
+    db::upsert_followings(temp.path(), &previously_followed, 60, "low")
+        .await
+        .unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        extra_orgs: vec!["ghost".into()],
+        ..test_config(temp.path(), &server.base_url())
+    };
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let bob_unfollowed = || {
+        Connection::open(temp.path())
+            .unwrap()
+            .query_row(
+                "SELECT unfollowed_at IS NOT NULL FROM users WHERE login = 'bob'",
+                [],
+                |row| row.get::<_, bool>(0),
+            )
+            .unwrap()
+    };
+
+    // A skipped org makes the roster incomplete, so nobody is retired.
+    poll_once(Arc::new(config.clone()), client.clone(), None)
+        .await
+        .unwrap();
+    assert!(!bob_unfollowed());
+    bob_starred.assert_hits_async(1).await;
+
+    let complete = Config {
+        extra_orgs: Vec::new(),
+        ..config
+    };
+    let keep = Config {
+        keep_unfollowed: true,
+        ..complete.clone()
+    };
+    poll_once(Arc::new(keep), client.clone(), None)
+        .await
+        .unwrap();
+    assert!(!bob_unfollowed());
+
+    poll_once(Arc::new(complete.clone()), client.clone(), None)
+        .await
+        .unwrap();
+    assert!(bob_unfollowed());
+
+    Connection::open(temp.path())
+        .unwrap()
+        .execute(
+            "UPDATE users SET next_check_at = '2000-01-01T00:00:00+00:00'",
+            [],
+        )
+        .unwrap();
+    poll_once(Arc::new(complete), client, None).await.unwrap();
+    bob_starred.assert_hits_async(1).await;
+}
 
 #[tokio::test]
-async fn github_client_returns_rate_limited_error() {
+async fn tracked_org_members_are_merged_with_followings() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .body("[{\"login\":\"alice\",\"id\":1},{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let members = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/orgs/acme/members")
+                .query_param("page", "1");
+            then.status(200)
+                .body("[{\"login\":\"bob\",\"id\":2},{\"login\":\"carol\",\"id\":3}]");
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/orgs/ghost/members");
+            then.status(404).body("{\"message\":\"Not Found\"}");
+        })
+        .await;
+    for login in ["alice", "bob", "carol"] {
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/users/{login}/starred"));
+                then.status(200).body("[]");
+            })
+            .await;
+    }
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        extra_orgs: vec!["acme".into(), "ghost".into()],
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    poll_once(Arc::new(config), client, None).await.unwrap();
+    members.assert_hits_async(1).await;
+
+    let mut users: Vec<_> = db::all_users(temp.path())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|user| (user.user_id, user.login))
+        .collect();
+    users.sort();
+    assert_eq!(
+        users,
+        vec![
+            (1, "alice".to_string()),
+            (2, "bob".to_string()),
+            (3, "carol".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn backfill_since_days_skips_older_stars_for_new_users() {
     let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"alice\",\"id\":1}]");
+        })
+        .await;
+    let star = |repo: &str, days_ago: i64| {
+        let starred_at = (Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339();
+        format!(
+            "{{\"starred_at\":\"{starred_at}\",\"repo\":{{\"full_name\":\"{repo}\",\"description\":null,\"html_url\":\"https://github.com/{repo}\",\"language\":null}}}}"
+        )
+    };
+    let body = format!("[{},{}]", star("acme/recent", 2), star("acme/ancient", 400));
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(200).body(body);
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        backfill_since_days: Some(30),
+        ..test_config(temp.path(), &server.base_url())
+    };
 
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    poll_once(Arc::new(config), client, None).await.unwrap();
+    let events = db::recent_events_for_feed(temp.path(), 10, None, None)
+        .await
+        .unwrap();
+    let repos: Vec<_> = events.iter().map(|e| e.repo_full_name.as_str()).collect();
+    assert_eq!(repos, vec!["acme/recent"]);
+}
+
+#[tokio::test]
+async fn ascending_backfill_keeps_pages_committed_before_a_failure() {
+    let server = MockServer::start_async().await;
     server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"alice\",\"id\":1}]");
+        })
+        .await;
+    let star = |index: i64| {
+        let starred_at = (Utc::now() - chrono::Duration::days(1000 - index)).to_rfc3339();
+        format!(
+            "{{\"starred_at\":\"{starred_at}\",\"repo\":{{\"full_name\":\"acme/repo{index}\",\"description\":null,\"html_url\":\"https://github.com/acme/repo{index}\",\"language\":null}}}}"
+        )
+    };
+    let first_page = format!("[{}]", (0..100).map(star).collect::<Vec<_>>().join(","));
+    let first = server
         .mock_async(|when, then| {
             when.method(GET)
                 .path("/users/alice/starred")
-                .query_param("per_page", "100")
+                .query_param("sort", "created")
+                .query_param("direction", "asc")
                 .query_param("page", "1");
-            then.status(403).header("Retry-After", "60");
+            then.status(200).body(first_page);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("direction", "asc")
+                .query_param("page", "2");
+            then.status(502).body("bad gateway");
         })
         .await;
 
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
     let config = Config {
-        github_token: "test-token".into(),
-        db_path: PathBuf::from("/tmp/ignored.db"),
-        max_concurrency: 1,
-        feed_length: 10,
-        default_interval_minutes: 60,
-        min_interval_minutes: 10,
-        max_interval_minutes: 7 * 24 * 60,
-        api_base_url: Url::parse(&server.base_url()).unwrap(),
-        user_agent: "following-stars-rss-test".into(),
-        timeout_secs: 5,
-        mode: Mode::Once,
+        max_concurrency: 2,
+        backfill_ascending: true,
+        ..test_config(temp.path(), &server.base_url())
     };
 
-    let client = GitHubClient::new(&config).unwrap();
-    let err = client
-        .fetch_starred("alice", None, None, None)
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    assert!(poll_once(Arc::new(config), client, None).await.is_err());
+    first.assert_async().await;
+
+    // The oldest page survives the failed second page.
+    let users = db::all_users(temp.path()).await.unwrap();
+    assert_eq!(users[0].star_count, 100);
+    let newest_stored = Utc::now() - chrono::Duration::days(901);
+    assert!(users[0].last_starred_at.unwrap() <= newest_stored);
+    let events = db::recent_events_for_feed(temp.path(), 1, None, None)
         .await
-        .expect_err("expected rate limit error");
+        .unwrap();
+    assert_eq!(events[0].repo_full_name, "acme/repo99");
+}
 
-    match err {
-        GitHubApiError::RateLimited(wait) => assert_eq!(wait.as_secs(), 60),
-        other => panic!("expected rate limited error, got {other:?}"),
+#[tokio::test]
+async fn max_pages_per_user_resumes_older_stars_on_later_polls() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"alice\",\"id\":1}]");
+        })
+        .await;
+    let now = Utc::now();
+    let star = |index: i64| {
+        let starred_at = (now - chrono::Duration::days(1000 - index)).to_rfc3339();
+        format!(
+            "{{\"starred_at\":\"{starred_at}\",\"repo\":{{\"full_name\":\"acme/repo{index}\",\"description\":null,\"html_url\":\"https://github.com/acme/repo{index}\",\"language\":null}}}}"
+        )
+    };
+    let page = |range: std::ops::Range<i64>, newest_first: bool| {
+        let mut stars: Vec<_> = range.map(star).collect();
+        if newest_first {
+            stars.reverse();
+        }
+        format!("[{}]", stars.join(","))
+    };
+    let newest = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("direction", "desc")
+                .query_param("page", "1");
+            then.status(200).body(page(50..150, true));
+        })
+        .await;
+    let oldest = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("direction", "asc")
+                .query_param("page", "1");
+            then.status(200).body(page(0..100, false));
+        })
+        .await;
+    let rest = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/users/alice/starred")
+                .query_param("direction", "asc")
+                .query_param("page", "2");
+            then.status(200).body(page(100..150, false));
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Arc::new(Config {
+        max_concurrency: 2,
+        max_pages_per_user: Some(1),
+        ..test_config(temp.path(), &server.base_url())
+    });
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+
+    // The first poll reads one newest-first page and leaves the rest for later.
+    poll_once(config.clone(), client.clone(), None)
+        .await
+        .unwrap();
+    newest.assert_async().await;
+    let users = db::all_users(temp.path()).await.unwrap();
+    assert_eq!(users[0].star_count, 100);
+    assert_eq!(users[0].backfill_page, Some(1));
+    assert!(users[0].next_check_at <= Utc::now());
+
+    // Each later poll reads one more page oldest-first until the walk ends.
+    poll_once(config.clone(), client.clone(), None)
+        .await
+        .unwrap();
+    oldest.assert_async().await;
+    let users = db::all_users(temp.path()).await.unwrap();
+    assert_eq!(users[0].star_count, 150);
+    assert_eq!(users[0].backfill_page, Some(2));
+
+    poll_once(config, client, None).await.unwrap();
+    rest.assert_async().await;
+    let users = db::all_users(temp.path()).await.unwrap();
+    assert_eq!(users[0].star_count, 150);
+    assert_eq!(users[0].backfill_page, None);
+    assert_eq!(newest.hits_async().await, 1);
+}
+
+#[tokio::test]
+async fn starred_repos_without_starred_at_fall_back_to_fetch_time() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200).body("[{\"login\":\"alice\",\"id\":1}]");
+        })
+        .await;
+    // A plain repository list, as returned when the star+json media type is not applied.
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(200).body(
+                "[{\"full_name\":\"acme/plain\",\"description\":null,\"html_url\":\"https://github.com/acme/plain\",\"language\":\"Rust\"}]",
+            );
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        max_concurrency: 2,
+        backfill: true,
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let config = Arc::new(config);
+    let before = Utc::now();
+    poll_once(Arc::clone(&config), client.clone(), None)
+        .await
+        .unwrap();
+    // A second pass sees the same repository with a new estimated time.
+    poll_once(config, client, None).await.unwrap();
+
+    let events = db::recent_events_for_feed(temp.path(), 10, None, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].repo_full_name, "acme/plain");
+    assert!(events[0].starred_at >= before);
+}
+
+#[tokio::test]
+async fn poll_deadline_skips_users_not_yet_started() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .body("[{\"login\":\"alice\",\"id\":1},{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let mut starred = Vec::new();
+    for login in ["alice", "bob"] {
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/users/{login}/starred"));
+                then.status(200)
+                    .delay(std::time::Duration::from_millis(1500))
+                    .body("[]");
+            })
+            .await;
+        starred.push(mock);
+    }
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        poll_deadline_secs: Some(1),
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let outcome = poll_once(Arc::new(config), client, None).await.unwrap();
+    assert_eq!(outcome, PollOutcome::Partial { skipped: 1 });
+    // The user already in flight when the deadline passed still finished.
+    let hits: usize = starred.iter().map(|mock| mock.hits()).sum();
+    assert_eq!(hits, 1);
+}
+
+#[tokio::test]
+async fn background_polls_keep_the_poll_deadline() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .body("[{\"login\":\"alice\",\"id\":1},{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let mut starred = Vec::new();
+    for login in ["alice", "bob"] {
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/users/{login}/starred"));
+                then.status(200)
+                    .delay(std::time::Duration::from_millis(1500))
+                    .body("[]");
+            })
+            .await;
+        starred.push(mock);
+    }
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        backfill: true,
+        backfill_since_days: Some(30),
+        poll_deadline_secs: Some(1),
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    // Refreshes drop the one-shot backfill settings but not the deadline.
+    let refresh = server::background_config(&config);
+    assert!(!refresh.backfill);
+    assert_eq!(refresh.backfill_since_days, None);
+    let client = Arc::new(GitHubClient::new(&refresh).unwrap());
+    let outcome = poll_once(Arc::new(refresh), client, None).await.unwrap();
+    assert_eq!(outcome, PollOutcome::Partial { skipped: 1 });
+    let hits: usize = starred.iter().map(|mock| mock.hits()).sum();
+    assert_eq!(hits, 1);
+}
+
+#[tokio::test]
+async fn a_failing_user_does_not_stop_the_rest_of_the_poll() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .body("[{\"login\":\"alice\",\"id\":1},{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let alice = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/alice/starred");
+            then.status(500).body("{\"message\":\"Server Error\"}");
+        })
+        .await;
+    let bob = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/users/bob/starred");
+            then.status(200).body("[]");
+        })
+        .await;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = test_config(temp.path(), &server.base_url());
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let err = poll_once(Arc::new(config), client, None)
+        .await
+        .expect_err("alice's failure is returned");
+    assert!(format!("{err:#}").contains("500"), "{err:#}");
+    assert_eq!(alice.hits(), 1);
+    assert_eq!(bob.hits(), 1);
+}
+
+#[tokio::test]
+async fn rate_limit_reserve_defers_users_until_reset() {
+    let server = MockServer::start_async().await;
+    let reset = (Utc::now() + chrono::Duration::minutes(30)).timestamp();
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/user/following");
+            then.status(200)
+                .header("x-ratelimit-remaining", "40")
+                .header("x-ratelimit-reset", reset.to_string())
+                .body("[{\"login\":\"alice\",\"id\":1},{\"login\":\"bob\",\"id\":2}]");
+        })
+        .await;
+    let mut starred = Vec::new();
+    for login in ["alice", "bob"] {
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/users/{login}/starred"));
+                then.status(200).body("[]");
+            })
+            .await;
+        starred.push(mock);
     }
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    db::init(temp.path()).await.unwrap();
+    let config = Config {
+        rate_limit_reserve: 50,
+        ..test_config(temp.path(), &server.base_url())
+    };
+
+    let client = Arc::new(GitHubClient::new(&config).unwrap());
+    let outcome = poll_once(Arc::new(config), client, None).await.unwrap();
+    // The followings response left 40 requests, under the reserve of 50.
+    assert_eq!(outcome, PollOutcome::Partial { skipped: 2 });
+    let hits: usize = starred.iter().map(|mock| mock.hits()).sum();
+    assert_eq!(hits, 0);
+}
+
+#[tokio::test]
+async fn websub_publisher_posts_feed_url_to_hub() {
+    let server = MockServer::start_async().await;
+    let hub = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/hub")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body("hub.mode=publish&hub.url=https%3A%2F%2Fexample.com%2Fhoshi%2Ffeed.xml");
+            then.status(204);
+        })
+        .await;
+    let rejecting = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/broken");
+            then.status(400).body("unknown topic");
+        })
+        .await;
+
+    let config = test_config(Path::new("/tmp/ignored.db"), &server.base_url());
+
+    let publisher = HubPublisher::new(
+        &config,
+        &server.url("/hub"),
+        "https://example.com/hoshi/feed.xml",
+    )
+    .unwrap();
+    publisher.publish().await.expect("hub accepts publish");
+    hub.assert_async().await;
+
+    let publisher = HubPublisher::new(
+        &config,
+        &server.url("/broken"),
+        "https://example.com/hoshi/feed.xml",
+    )
+    .unwrap();
+    let err = publisher.publish().await.expect_err("hub rejects publish");
+    assert!(err.to_string().contains("unknown topic"), "{err}");
+    rejecting.assert_async().await;
 }
 
 #[test]
@@ -62,6 +1197,7 @@ fn feed_builder_includes_expected_fields() {
         repo_language: Some("Rust".into()),
         repo_topics: vec!["compiler".into()],
         repo_html_url: "https://github.com/rust-lang/rust".into(),
+        repo_owner_avatar_url: Some("https://avatars.githubusercontent.com/u/5430905?v=4".into()),
         starred_at: Utc.with_ymd_and_hms(2025, 10, 18, 4, 15, 0).unwrap(),
         fetched_at: Utc.with_ymd_and_hms(2025, 10, 18, 4, 16, 0).unwrap(),
         user_activity_tier: Some("high".into()),
@@ -71,6 +1207,7 @@ fn feed_builder_includes_expected_fields() {
     let xml = feed::build_feed(
         &events,
         Utc.with_ymd_and_hms(2025, 10, 18, 5, 0, 0).unwrap(),
+        &FeedOptions::default(),
     )
     .expect("feed build");
 
@@ -78,17 +1215,37 @@ fn feed_builder_includes_expected_fields() {
     assert!(xml.contains("alice starred rust-lang/rust"));
     assert!(xml.contains("github-star://alice/rust-lang/rust"));
     assert!(xml.contains("Rust programming language"));
+    assert!(xml.contains("<content:encoded>"));
+    assert!(xml.contains("avatars.githubusercontent.com/u/5430905"));
+    assert!(!xml.contains("<enclosure"));
 
-    let html = feed::build_html(
+    let with_enclosures = feed::build_feed(
         &events,
         Utc.with_ymd_and_hms(2025, 10, 18, 5, 0, 0).unwrap(),
-        "",
-    );
-    assert!(html.contains("GitHub Followings Stars"));
-    assert!(html.contains("id=\"search-input\""));
-    assert!(html.contains("id=\"language-filter\""));
-    assert!(html.contains("Sort: Newest"));
-    assert!(html.contains("Last updated"));
+        &FeedOptions {
+            enclosures: true,
+            ..FeedOptions::default()
+        },
+    )
+    .expect("feed build");
+    assert!(with_enclosures.contains(
+        "<enclosure url=\"https://opengraph.githubassets.com/1/rust-lang/rust\" length=\"0\" type=\"image/png\"/>"
+    ));
+
+    #[cfg(feature = "frontend")]
+    {
+        let html = feed::build_html(
+            &events,
+            Utc.with_ymd_and_hms(2025, 10, 18, 5, 0, 0).unwrap(),
+            "",
+            None,
+        );
+        assert!(html.contains("GitHub Followings Stars"));
+        assert!(html.contains("id=\"search-input\""));
+        assert!(html.contains("id=\"language-filter\""));
+        assert!(html.contains("Sort: Newest"));
+        assert!(html.contains("Last updated"));
+    }
 }
 
 #[tokio::test]
@@ -113,20 +1270,15 @@ async fn server_routes_serve_feed_and_html() {
 
     let config = Arc::new(Config {
         github_token: "token".into(),
-        db_path: temp.path().to_path_buf(),
-        max_concurrency: 1,
-        feed_length: 10,
-        default_interval_minutes: 60,
-        min_interval_minutes: 10,
-        max_interval_minutes: 7 * 24 * 60,
-        api_base_url: Url::parse("https://api.github.com").unwrap(),
-        user_agent: "following-stars-rss-test".into(),
-        timeout_secs: 5,
-        mode: Mode::Once,
+        ..test_config(temp.path(), "https://api.github.com")
     });
 
     let scheduler = Arc::new(SchedulerState::new(15));
-    let state = Arc::new(AppState::new(config, scheduler, None, String::new()));
+    let frozen = Utc.with_ymd_and_hms(2025, 10, 18, 6, 0, 0).unwrap();
+    let state = Arc::new(
+        AppState::new(config, scheduler, None, String::new())
+            .with_clock(Arc::new(FixedClock(frozen))),
+    );
     let routes = server::routes(state);
 
     let feed_resp = warp::test::request().path("/feed.xml").reply(&routes).await;
@@ -137,8 +1289,18 @@ async fn server_routes_serve_feed_and_html() {
     );
     let feed_body = String::from_utf8(feed_resp.body().to_vec()).unwrap();
     assert!(feed_body.contains("rust-lang/rust"));
+    assert!(
+        feed_body.contains("<lastBuildDate>Sat, 18 Oct 2025 06:00:00 +0000</lastBuildDate>"),
+        "{feed_body}"
+    );
+    let again = warp::test::request().path("/feed.xml").reply(&routes).await;
+    assert_eq!(again.body(), feed_resp.body());
 
     let html_resp = warp::test::request().path("/").reply(&routes).await;
+    if cfg!(not(feature = "frontend")) {
+        assert_eq!(html_resp.status(), StatusCode::NOT_FOUND);
+        return;
+    }
     assert_eq!(html_resp.status(), StatusCode::OK);
     assert_eq!(
         html_resp.headers().get("content-type").unwrap(),